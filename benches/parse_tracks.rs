@@ -0,0 +1,49 @@
+//! Compares parsing a large playlist into owned `Track`s against borrowing `TrackLite`s.
+//!
+//! Run with `cargo bench --bench parse_tracks`.
+use rstidal::client::{Tidal, TidalItems};
+use rstidal::model::track::{Track, TrackLite};
+
+use std::time::Instant;
+
+const TRACKS: usize = 1000;
+const ROUNDS: u32 = 200;
+
+fn playlist() -> String {
+    let page: serde_json::Value =
+        serde_json::from_str(include_str!("../tests/files/playlist_tracks.json")).unwrap();
+    let items = page["items"].as_array().unwrap();
+    let items: Vec<_> = items.iter().cycle().take(TRACKS).collect();
+    serde_json::json!({
+        "limit": TRACKS,
+        "offset": 0,
+        "totalNumberOfItems": TRACKS,
+        "items": items,
+    })
+    .to_string()
+}
+
+fn time<F: FnMut() -> usize>(name: &str, mut parse: F) {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        assert_eq!(parse(), TRACKS);
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+    println!("{:<10} {:>10.3?} per {} tracks", name, elapsed, TRACKS);
+}
+
+fn main() {
+    let input = playlist();
+    time("Track", || {
+        Tidal::convert_result::<TidalItems<Track>>(&input)
+            .unwrap()
+            .items
+            .len()
+    });
+    time("TrackLite", || {
+        Tidal::convert_result::<TidalItems<TrackLite>>(&input)
+            .unwrap()
+            .items
+            .len()
+    });
+}
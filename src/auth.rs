@@ -8,11 +8,36 @@ use mockito;
 
 // Use built-in library
 use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct TidalCredentials {
     pub token: String,
     pub session: Option<Session>,
+    // Username and password to log in again with once the session expired, see
+    // `reauthenticate_with`
+    pub(crate) login: Option<(String, String)>,
+}
+
+// Keeps the start of a secret, enough to tell secrets apart in logs without leaking them
+fn redact(secret: &str) -> String {
+    format!("{}***", secret.chars().take(3).collect::<String>())
+}
+
+impl fmt::Debug for TidalCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TidalCredentials")
+            .field("token", &redact(&self.token))
+            .field("session", &self.session)
+            .field("login", &self.login.as_ref().map(|(username, _)| (username, "***")))
+            .finish()
+    }
 }
 
 impl TidalCredentials {
@@ -21,6 +46,7 @@ impl TidalCredentials {
         Self {
             token: token.to_owned(),
             session: None,
+            login: None,
         }
     }
 
@@ -29,6 +55,18 @@ impl TidalCredentials {
         self
     }
 
+    /// Opts in to logging in again with `username` and `password` when Tidal rejects the
+    /// session. The request that failed is retried once with the new session.
+    #[must_use]
+    pub fn reauthenticate_with(mut self, username: &str, password: &str) -> Self {
+        self.login = Some((username.to_owned(), password.to_owned()));
+        self
+    }
+
+    /// Logs in with `username` and `password`.
+    ///
+    /// A failed login is swallowed and leaves the credentials without a session, which only
+    /// surfaces once the credentials are used to build a client. Prefer `try_create_session`.
     #[must_use]
     pub async fn create_session(self, username: &str, password: &str) -> Self {
         if self.token.is_empty() {
@@ -39,6 +77,91 @@ impl TidalCredentials {
         let session = Session::get_session(&token, username, password).await.ok();
         self.session(session)
     }
+
+    /// Logs in with `username` and `password`, failing when no application token is set or
+    /// Tidal rejects the login or can't be reached.
+    pub async fn try_create_session(
+        self,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, AuthError> {
+        if self.token.is_empty() {
+            return Err(AuthError::MissingToken);
+        }
+        let session = Session::get_session(&self.token, username, password).await?;
+        Ok(self.session(Some(session)))
+    }
+
+    /// Starts an OAuth device authorization. The application token is used as the OAuth
+    /// `client_id`. Show `user_code` and `verification_uri` to the user and call
+    /// `DeviceFlow::poll` to wait for them to authorize the device.
+    pub async fn device_login(&self) -> Result<DeviceFlow, AuthError> {
+        if self.token.is_empty() {
+            return Err(AuthError::MissingToken);
+        }
+        let mut payload: HashMap<&str, &str> = HashMap::new();
+        payload.insert("client_id", &self.token);
+        payload.insert("scope", "r_usr w_usr");
+
+        let url = format!("{}/oauth2/device_authorization", auth_base_url());
+        let response = Client::new().post(&url).form(&payload).send().await?;
+
+        if response.status().is_success() {
+            debug!("response content: {:?}", response);
+            let mut flow: DeviceFlow = response.json().await?;
+            flow.client_id = self.token.to_owned();
+            Ok(flow)
+        } else {
+            error!("Device authorization failed. token: {:?}", redact(&self.token));
+            error!("{:?}", response);
+            Err(AuthError::CreateSessionFailed)
+        }
+    }
+
+    /// Writes the session to `path` as JSON so it can be restored with `load_and_validate`.
+    /// The file holds the access and refresh tokens, on unix it's only readable and writable by
+    /// its owner (`0600`).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), AuthError> {
+        let session = self.session.as_ref().ok_or(AuthError::MissingSession)?;
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(path)?;
+        // `mode` only applies to new files, an existing one may be readable by others
+        #[cfg(unix)]
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+
+        file.write_all(serde_json::to_string(session)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Restores a session saved with `save` and checks it's still valid. An invalid, stale or
+    /// partially corrupt session is refreshed with its refresh token and saved again.
+    pub async fn load_and_validate(token: &str, path: impl AsRef<Path>) -> Result<Self, AuthError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+
+        if let Ok(session) = serde_json::from_str::<Session>(&contents) {
+            if session.validate().await {
+                return Ok(Self::new(token).session(Some(session)));
+            }
+        }
+
+        // Even a corrupt session can be recovered as long as the refresh token is readable
+        let refresh_token = serde_json::from_str::<serde_json::Value>(&contents)
+            .ok()
+            .and_then(|session| session.get("refreshToken")?.as_str().map(str::to_owned))
+            .ok_or(AuthError::SessionRecoveryFailed)?;
+
+        let session = Session::refresh(token, &refresh_token)
+            .await
+            .map_err(|_| AuthError::SessionRecoveryFailed)?;
+        let credentials = Self::new(token).session(Some(session));
+        credentials.save(path)?;
+        Ok(credentials)
+    }
 }
 
 //Tidal session example:
@@ -53,15 +176,45 @@ pub enum AuthError {
     #[error("The Authe request Failed")]
     AuthRequestFailed { #[from] source: reqwest::Error },
     #[error("Fetch session failed")]
-    CreateSessionFailed
+    CreateSessionFailed,
+    #[error("The application token needs to be set")]
+    MissingToken,
+    #[error("There is no session to save")]
+    MissingSession,
+    #[error("The device code expired before it was authorized")]
+    DeviceCodeExpired,
+    #[error("Device authorization failed: {0}")]
+    DeviceAuthorizationFailed(String),
+    #[error("Reading or writing the session failed")]
+    Persistence { #[from] source: std::io::Error },
+    #[error("The session could not be parsed")]
+    ParseSession { #[from] source: serde_json::Error },
+    #[error("The session is invalid and could not be refreshed")]
+    SessionRecoveryFailed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Session {
     pub user_id: u32,
     pub session_id: String,
     pub country_code: String,
+    /// OAuth access token, only present for sessions created through `DeviceFlow`.
+    pub access_token: Option<String>,
+    /// OAuth refresh token, only present for sessions created through `DeviceFlow`.
+    pub refresh_token: Option<String>,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("user_id", &self.user_id)
+            .field("session_id", &redact(&self.session_id))
+            .field("country_code", &self.country_code)
+            .field("access_token", &self.access_token.as_deref().map(redact))
+            .field("refresh_token", &self.refresh_token.as_deref().map(redact))
+            .finish()
+    }
 }
 
 impl Session {
@@ -95,20 +248,198 @@ impl Session {
             let session: Session = response.json().await?;
             Ok(session)
         } else {
-            error!(
-                "Creating session failed. token: {:?}, form: {:?}",
-                &token, &payload
-            );
+            // The form holds the username and password, neither may end up in the logs
+            error!("Creating session failed. token: {:?}", redact(&token));
+            error!("{:?}", response);
+            Err(AuthError::CreateSessionFailed)
+        }
+    }
+
+    /// Checks the session against Tidal's `/sessions` endpoint.
+    pub async fn validate(&self) -> bool {
+        let url = format!("{}/sessions", api_base_url());
+        let request = Client::new().get(&url);
+        let request = if let Some(access_token) = &self.access_token {
+            request.bearer_auth(access_token)
+        } else {
+            request.header("X-Tidal-SessionId", &self.session_id)
+        };
+
+        match request.send().await {
+            Ok(response) => response.status().is_success(),
+            Err(err) => {
+                error!("Validating session failed: {:?}", err);
+                false
+            }
+        }
+    }
+
+    /// Creates a new session from an OAuth refresh token.
+    pub async fn refresh(token: &str, refresh_token: &str) -> Result<Self, AuthError> {
+        let mut payload: HashMap<&str, &str> = HashMap::new();
+        payload.insert("client_id", token);
+        payload.insert("refresh_token", refresh_token);
+        payload.insert("grant_type", "refresh_token");
+        payload.insert("scope", "r_usr w_usr");
+
+        let url = format!("{}/oauth2/token", auth_base_url());
+        let response = Client::new().post(&url).form(&payload).send().await?;
+
+        if response.status().is_success() {
+            debug!("response content: {:?}", response);
+            let device_token: DeviceToken = response.json().await?;
+            Ok(device_token.into_session(Some(refresh_token)))
+        } else {
+            error!("Refreshing session failed. token: {:?}", redact(token));
             error!("{:?}", response);
             Err(AuthError::CreateSessionFailed)
         }
     }
 }
 
+#[cfg(not(test))]
+fn api_base_url() -> String {
+    "https://api.tidalhifi.com/v1".to_owned()
+}
+
+#[cfg(test)]
+fn api_base_url() -> String {
+    mockito::server_url()
+}
+
+#[cfg(not(test))]
+fn auth_base_url() -> String {
+    "https://auth.tidal.com/v1".to_owned()
+}
+
+#[cfg(test)]
+fn auth_base_url() -> String {
+    mockito::server_url()
+}
+
+//Tidal device authorization example:
+//{
+    //"deviceCode": "6b3c1a2e-0c1d-4a3b-9f3e-2f1a0b9c8d7e",
+    //"userCode": "ABCDE",
+    //"verificationUri": "link.tidal.com",
+    //"expiresIn": 300,
+    //"interval": 2
+//}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceFlow {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    /// Seconds to wait between polls, RFC 8628 defaults it to 5 when Tidal doesn't send it.
+    #[serde(default = "default_poll_interval")]
+    pub interval: u64,
+    #[serde(skip)]
+    client_id: String,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+/// The outcome of a single request to the token endpoint.
+#[derive(Debug)]
+pub enum DevicePoll {
+    /// The user authorized the device.
+    Authorized(Session),
+    /// The user has not authorized the device yet.
+    Pending,
+    /// Polling too fast, the interval needs to be increased.
+    SlowDown,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceToken {
+    access_token: String,
+    refresh_token: Option<String>,
+    user: DeviceTokenUser,
+}
+
+impl DeviceToken {
+    // Refreshing doesn't always return a new refresh token, the previous one stays valid then
+    fn into_session(self, refresh_token: Option<&str>) -> Session {
+        Session {
+            user_id: self.user.user_id,
+            session_id: String::new(),
+            country_code: self.user.country_code,
+            access_token: Some(self.access_token),
+            refresh_token: self.refresh_token.or_else(|| refresh_token.map(str::to_owned)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceTokenUser {
+    user_id: u32,
+    country_code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceTokenError {
+    error: String,
+}
+
+impl DeviceFlow {
+    /// Polls the token endpoint every `interval` seconds until the user authorizes the
+    /// device or the device code expires.
+    pub async fn poll(&self) -> Result<Session, AuthError> {
+        let started = Instant::now();
+        let expires_in = Duration::from_secs(self.expires_in);
+        let mut interval = Duration::from_secs(self.interval);
+
+        loop {
+            match self.poll_once().await? {
+                DevicePoll::Authorized(session) => return Ok(session),
+                DevicePoll::Pending => {}
+                // RFC 8628 asks clients to add 5 seconds to the interval on `slow_down`
+                DevicePoll::SlowDown => interval += Duration::from_secs(5),
+            }
+            if started.elapsed() + interval >= expires_in {
+                return Err(AuthError::DeviceCodeExpired);
+            }
+            tokio::time::delay_for(interval).await;
+        }
+    }
+
+    /// Makes a single request to the token endpoint.
+    pub async fn poll_once(&self) -> Result<DevicePoll, AuthError> {
+        let mut payload: HashMap<&str, &str> = HashMap::new();
+        payload.insert("client_id", &self.client_id);
+        payload.insert("device_code", &self.device_code);
+        payload.insert("grant_type", "urn:ietf:params:oauth:grant-type:device_code");
+        payload.insert("scope", "r_usr w_usr");
+
+        let url = format!("{}/oauth2/token", auth_base_url());
+        let response = Client::new().post(&url).form(&payload).send().await?;
+
+        if response.status().is_success() {
+            debug!("response content: {:?}", response);
+            let token: DeviceToken = response.json().await?;
+            return Ok(DevicePoll::Authorized(token.into_session(None)));
+        }
+
+        let DeviceTokenError { error } = response.json().await?;
+        match error.as_str() {
+            "authorization_pending" => Ok(DevicePoll::Pending),
+            "slow_down" => Ok(DevicePoll::SlowDown),
+            "expired_token" => Err(AuthError::DeviceCodeExpired),
+            _ => Err(AuthError::DeviceAuthorizationFailed(error)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use mockito::mock;
+    use mockito::{mock, Matcher};
 
     #[test]
     fn test_credential_set_new() {
@@ -116,12 +447,35 @@ mod tests {
         assert_eq!(credentials.token, "some_token".to_owned());
     }
 
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let session = Session {
+            user_id: 1234,
+            session_id: "session-id-1".to_owned(),
+            country_code: "US".to_owned(),
+            access_token: Some("access-token-1".to_owned()),
+            refresh_token: None,
+        };
+        let credentials = TidalCredentials::new("some_token")
+            .session(Some(session))
+            .reauthenticate_with("user@example.com", "hunter2");
+
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains(r#"token: "som***""#));
+        assert!(debug.contains("user@example.com"));
+        for secret in &["some_token", "session-id-1", "access-token-1", "hunter2"] {
+            assert!(!debug.contains(secret), "{} leaked in {}", secret, debug);
+        }
+    }
+
     #[test]
     fn test_credential_set_session_info() {
         let session = Session {
             user_id: 1234,
             session_id: "xq123".to_owned(),
             country_code: "US".to_owned(),
+            access_token: None,
+            refresh_token: None,
         };
         let credentials = TidalCredentials::new("some_token").session(Some(session));
         assert_eq!(credentials.session.is_some(), true);
@@ -152,6 +506,186 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_credential_try_create_session() {
+        let credentials = TidalCredentials::new("some_token");
+
+        {
+            let _mock = mock_successful_login();
+            let credentials = credentials
+                .clone()
+                .try_create_session("myuser@example.com", "somepawssowrd")
+                .await
+                .unwrap();
+            assert_eq!(credentials.session.unwrap().session_id, "session-id-123");
+        }
+        {
+            let _mock = mock_failed_login();
+            let result = credentials
+                .clone()
+                .try_create_session("myuser@example.com", "wrongpassword")
+                .await;
+            assert!(matches!(result, Err(AuthError::CreateSessionFailed)));
+        }
+        {
+            let result = TidalCredentials::new("")
+                .try_create_session("myuser@example.com", "somepawssowrd")
+                .await;
+            assert!(matches!(result, Err(AuthError::MissingToken)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_device_login_poll_pending_then_success() {
+        let _mock_device = mock("POST", "/oauth2/device_authorization")
+            .with_status(200)
+            .with_body(
+                r#"{"deviceCode": "device-code-1", "userCode": "ABCDE", "verificationUri": "link.tidal.com", "expiresIn": 300, "interval": 0}"#,
+            )
+            .create();
+        let mock_pending = mock("POST", "/oauth2/token")
+            .with_status(400)
+            .with_body(r#"{"status": 400, "error": "authorization_pending", "sub_status": 1002}"#)
+            .expect(1)
+            .create();
+        let mock_authorized = mock("POST", "/oauth2/token")
+            .with_status(200)
+            .with_body(
+                r#"{"access_token": "access-token-1", "token_type": "Bearer", "expires_in": 604800, "user": {"userId": 123, "countryCode": "US"}}"#,
+            )
+            .expect(1)
+            .create();
+
+        let flow = TidalCredentials::new("some_token").device_login().await.unwrap();
+        assert_eq!(flow.user_code, "ABCDE");
+
+        let session = flow.poll().await.unwrap();
+        mock_pending.assert();
+        mock_authorized.assert();
+        assert_eq!(session.user_id, 123);
+        assert_eq!(session.access_token.unwrap(), "access-token-1");
+    }
+
+    #[tokio::test]
+    async fn test_device_login_without_token() {
+        let result = TidalCredentials::new("").device_login().await;
+        assert!(matches!(result, Err(AuthError::MissingToken)));
+    }
+
+    #[test]
+    fn test_device_flow_default_interval() {
+        let flow: DeviceFlow = serde_json::from_str(
+            r#"{"deviceCode": "device-code-1", "userCode": "ABCDE", "verificationUri": "link.tidal.com", "expiresIn": 300}"#,
+        )
+        .unwrap();
+        assert_eq!(flow.interval, 5);
+    }
+
+    #[tokio::test]
+    async fn test_device_poll_slow_down() {
+        let _mock = mock("POST", "/oauth2/token")
+            .with_status(400)
+            .with_body(r#"{"status": 400, "error": "slow_down", "sub_status": 1002}"#)
+            .create();
+
+        let flow = DeviceFlow {
+            device_code: "device-code-1".to_owned(),
+            user_code: "ABCDE".to_owned(),
+            verification_uri: "link.tidal.com".to_owned(),
+            expires_in: 300,
+            interval: 0,
+            client_id: "some_token".to_owned(),
+        };
+        let result = flow.poll_once().await.unwrap();
+        assert!(matches!(result, DevicePoll::SlowDown));
+    }
+
+    #[test]
+    fn test_save_session() {
+        let path = std::env::temp_dir().join("rstidal_saved_session.json");
+        fs::write(&path, "").unwrap();
+        let session = Session {
+            user_id: 123,
+            session_id: "session-id-123".to_owned(),
+            country_code: "US".to_owned(),
+            access_token: None,
+            refresh_token: Some("refresh-token-1".to_owned()),
+        };
+        TidalCredentials::new("some_token").session(Some(session)).save(&path).unwrap();
+
+        let saved: Session = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.refresh_token.unwrap(), "refresh-token-1");
+        #[cfg(unix)]
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        fs::remove_file(&path).unwrap();
+
+        let result = TidalCredentials::new("some_token").save(&path);
+        assert!(matches!(result, Err(AuthError::MissingSession)));
+    }
+
+    #[tokio::test]
+    async fn test_load_and_validate_valid_session() {
+        let path = std::env::temp_dir().join("rstidal_valid_session.json");
+        fs::write(&path, r#"{"userId": 123, "sessionId": "session-id-123", "countryCode": "US"}"#)
+            .unwrap();
+        let _mock = mock("GET", "/sessions")
+            .match_header("x-tidal-sessionid", "session-id-123")
+            .with_status(200)
+            .with_body(r#"{"userId": 123, "sessionId": "session-id-123", "countryCode": "US"}"#)
+            .create();
+
+        let credentials = TidalCredentials::load_and_validate("some_token", &path).await.unwrap();
+        assert_eq!(credentials.session.unwrap().session_id, "session-id-123");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_load_and_validate_refreshes_stale_session() {
+        let path = std::env::temp_dir().join("rstidal_stale_session.json");
+        fs::write(
+            &path,
+            r#"{"userId": 123, "sessionId": "", "countryCode": "US", "accessToken": "access-token-1", "refreshToken": "refresh-token-1"}"#,
+        )
+        .unwrap();
+        let _mock_validate = mock("GET", "/sessions")
+            .match_header("authorization", "Bearer access-token-1")
+            .with_status(401)
+            .with_body(r#"{"status": 401, "subStatus": 11003, "userMessage": "Token expired"}"#)
+            .create();
+        let _mock_refresh = mock("POST", "/oauth2/token")
+            .match_body(Matcher::Regex("refresh_token=refresh-token-1".to_owned()))
+            .with_status(200)
+            .with_body(
+                r#"{"access_token": "access-token-2", "token_type": "Bearer", "expires_in": 604800, "user": {"userId": 123, "countryCode": "US"}}"#,
+            )
+            .create();
+
+        let credentials = TidalCredentials::load_and_validate("some_token", &path).await.unwrap();
+        let session = credentials.session.unwrap();
+        assert_eq!(session.access_token.unwrap(), "access-token-2");
+        assert_eq!(session.refresh_token.unwrap(), "refresh-token-1");
+
+        // The repaired session is saved again
+        let saved: Session = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.access_token.unwrap(), "access-token-2");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_load_and_validate_unrecoverable_session() {
+        let path = std::env::temp_dir().join("rstidal_unrecoverable_session.json");
+        fs::write(&path, r#"{"userId": 123, "sessionId": "session-id-123", "countryCode": "US"}"#)
+            .unwrap();
+        let _mock = mock("GET", "/sessions")
+            .with_status(401)
+            .with_body(r#"{"status": 401, "subStatus": 11003, "userMessage": "Session expired"}"#)
+            .create();
+
+        let result = TidalCredentials::load_and_validate("some_token", &path).await;
+        assert!(matches!(result, Err(AuthError::SessionRecoveryFailed)));
+        fs::remove_file(&path).unwrap();
+    }
+
     fn mock_successful_login() -> mockito::Mock {
         mock("POST", "/?token=some_token")
             .with_status(200)
@@ -1,23 +1,40 @@
 // Use 3rd party
+use bytes::Bytes;
+use futures_util::stream::{self, Stream, StreamExt};
 use log::{debug, warn};
-use reqwest::header::HeaderMap;
-use reqwest::{Client, Method, Response, StatusCode};
-use serde::Deserialize;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Client, Method, Proxy, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
 use thiserror::Error;
+use tokio::sync::Semaphore;
 
 #[cfg(test)]
 use mockito;
 
 // Use built-in library
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 // Use internal modules
 use crate::auth::{Session, TidalCredentials};
 use crate::model::album::Album;
 use crate::model::artist::Artist;
+use crate::model::credit::Contributor;
 use crate::model::playlist::Playlist;
+use crate::model::row::{AlbumRow, ArtistRow, PlaylistRow, TrackRow};
 use crate::model::track::Track;
+use crate::model::user::User;
+use crate::model::video::Video;
+
+/// `User-Agent` sent with every request unless `TidalBuilder::user_agent` overrides it.
+pub const DEFAULT_USER_AGENT: &str = "TIDAL/1.0 (rstidal)";
+
+// Maximum number of characters of a response body kept in `ClientError::Http`
+const MAX_ERROR_BODY_LENGTH: usize = 1024;
 
 // Possible errors returned from `rstidal` client.
 #[derive(Debug, Error)]
@@ -34,17 +51,68 @@ pub enum ClientError {
     Request(#[from] reqwest::Error),
     #[error("status code: {0}")]
     StatusCode(StatusCode),
+    #[error("http error: {status}: {body}")]
+    Http {
+        status: StatusCode,
+        /// The `x-tidal-*` headers of the response.
+        headers: HeaderMap,
+        /// The start of the response body.
+        body: String,
+    },
+    #[error("invalid limit: limit must be greater than 0")]
+    InvalidLimit,
+    #[error("invalid barcode: {0}")]
+    InvalidBarcode(String),
+    #[error("invalid id: {0}")]
+    InvalidId(String),
+    #[error("search terms can't be empty")]
+    EmptyQuery,
+    #[error("not a link to a Tidal track, album, artist or playlist: {0}")]
+    UnsupportedUrl(String),
+    #[error("a session needs to be obtained before using Tidal")]
+    MissingSession,
+    #[error("missing field in response: {0}")]
+    MissingField(&'static str),
+    #[error("unsupported stream manifest: {0}")]
+    UnsupportedManifest(String),
 }
 
 impl ClientError {
     async fn from_response(response: Response) -> Self {
-        match response.status() {
-            StatusCode::UNAUTHORIZED => Self::Unauthorized,
-            status @ StatusCode::FORBIDDEN | status @ StatusCode::NOT_FOUND => response
-                .json::<ApiError>()
-                .await
-                .map_or_else(|_| status.into(), Into::into),
-            status => status.into(),
+        let status = response.status();
+        if status == StatusCode::UNAUTHORIZED {
+            return Self::Unauthorized;
+        }
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in response.headers() {
+            if name.as_str().starts_with("x-tidal-") {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        let body = response.text().await.unwrap_or_default();
+
+        if let Ok(api_error) = serde_json::from_str::<ApiError>(&body) {
+            return api_error.into();
+        }
+        // Error pages can be whole HTML documents, only keep the start of them
+        Self::Http {
+            status,
+            headers,
+            body: body.chars().take(MAX_ERROR_BODY_LENGTH).collect(),
+        }
+    }
+}
+
+impl ClientError {
+    /// Whether Tidal answered that the requested resource doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::Api(ApiError::Regular { status, .. }) => *status == 404,
+            Self::Http { status, .. } | Self::StatusCode(status) => {
+                *status == StatusCode::NOT_FOUND
+            }
+            _ => false,
         }
     }
 }
@@ -55,20 +123,195 @@ impl From<StatusCode> for ClientError {
     }
 }
 #[derive(Debug, Error, Deserialize)]
+#[serde(untagged)]
 pub enum ApiError {
     #[error("{status}: {message}")]
     Regular {
         status: u16,
+        /// Tidal's more specific error code, e.g. 2001 for a missing resource.
+        #[serde(rename = "subStatus", default)]
+        sub_status: Option<u32>,
         #[serde(alias = "userMessage")]
         message: String,
     },
 }
 
+impl ApiError {
+    /// Named kind of the error, derived from `status` and `sub_status`.
+    pub fn code(&self) -> TidalErrorCode {
+        match self {
+            Self::Regular { status, sub_status, .. } => TidalErrorCode::new(*status, *sub_status),
+        }
+    }
+}
+
+/// Kinds of errors Tidal answers with, see `ApiError::code`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TidalErrorCode {
+    /// The requested resource doesn't exist.
+    NotFound,
+    /// The session expired or was revoked, a new login is needed.
+    SessionExpired,
+    /// Any other rejected authentication.
+    Unauthorized,
+    /// The content isn't available in the session's country.
+    GeoRestricted,
+    /// Too many requests were made in a short time.
+    RateLimited,
+    /// An error not known to this crate, holding its `status`.
+    Unknown(u16),
+}
+
+impl TidalErrorCode {
+    fn new(status: u16, sub_status: Option<u32>) -> Self {
+        match (status, sub_status) {
+            (_, Some(6001)) | (401, Some(11003)) => Self::SessionExpired,
+            (_, Some(5003)) => Self::GeoRestricted,
+            (401, _) => Self::Unauthorized,
+            (404, _) => Self::NotFound,
+            (429, _) => Self::RateLimited,
+            (status, _) => Self::Unknown(status),
+        }
+    }
+}
+
 pub type ClientResult<T> = Result<T, ClientError>;
 
+/// Number of items requested from endpoints returning a page of items when no `limit` is given,
+/// see `TidalBuilder::default_limit`.
+pub const DEFAULT_LIMIT: u16 = 10;
+
+/// Largest `limit` sent to endpoints returning a page of items, see `TidalBuilder::max_limit`.
+pub const DEFAULT_MAX_LIMIT: u16 = 50;
+
+/// Longest time waited before a retry, however long `TidalBuilder::retry_backoff` grows.
+pub const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+// Header value hidden from `Debug` output, e.g. when reqwest logs the request
+fn sensitive(value: &str) -> HeaderValue {
+    let mut value: HeaderValue = value.parse().unwrap();
+    value.set_sensitive(true);
+    value
+}
+
+// Time waited before retry number `retries + 1`, doubling `backoff` without overflowing
+fn retry_delay(backoff: Duration, retries: u32) -> Duration {
+    2u32.checked_pow(retries)
+        .and_then(|factor| backoff.checked_mul(factor))
+        .map_or(MAX_RETRY_BACKOFF, |delay| delay.min(MAX_RETRY_BACKOFF))
+}
+
+/// Whether a request answered with `status` is worth repeating: rate limited requests (429) and
+/// temporary server errors (500, 502, 503 and 504). This is the default policy of
+/// `TidalBuilder::retry_policy`.
+pub fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Builds the query parameters of a paginated request.
+///
+/// - A `limit` of 0 is rejected with `ClientError::InvalidLimit`.
+/// - When no `limit` is given, `default_limit` is used, also when only an `offset` is given.
+/// - A `limit` above `max_limit` is lowered to `max_limit`, Tidal rejects it otherwise.
+/// - `offset` is only sent when given.
+pub(crate) fn pagination_params(
+    limit: Option<u16>,
+    offset: Option<u32>,
+    default_limit: u16,
+    max_limit: u16,
+) -> ClientResult<HashMap<String, String>> {
+    let mut limit = limit.unwrap_or(default_limit);
+    if limit == 0 {
+        return Err(ClientError::InvalidLimit);
+    }
+    if limit > max_limit {
+        warn!("limit {} is above the maximum, using {} instead", limit, max_limit);
+        limit = max_limit;
+    }
+
+    let mut params: HashMap<String, String> = HashMap::new();
+    params.insert("limit".to_owned(), limit.to_string());
+    if let Some(offset) = offset {
+        params.insert("offset".to_owned(), offset.to_string());
+    }
+    Ok(params)
+}
+
+/// Trims a search term and checks something is left to search for.
+pub(crate) fn search_term(term: &str) -> ClientResult<&str> {
+    let trimmed = term.trim();
+    if trimmed.is_empty() {
+        Err(ClientError::EmptyQuery)
+    } else {
+        Ok(trimmed)
+    }
+}
+
+/// Trims a playlist id and checks it's a UUID (`8-4-4-4-12` hex digits).
+pub(crate) fn playlist_id(id: &str) -> ClientResult<&str> {
+    let trimmed = id.trim();
+    let groups: Vec<&str> = trimmed.split('-').collect();
+    let is_uuid = groups.len() == 5
+        && groups
+            .iter()
+            .zip(&[8, 4, 4, 4, 12])
+            .all(|(group, len)| {
+                group.len() == *len && group.bytes().all(|byte| byte.is_ascii_hexdigit())
+            });
+
+    if is_uuid {
+        Ok(trimmed)
+    } else {
+        Err(ClientError::InvalidId(id.to_owned()))
+    }
+}
+
+/// Trims an artist, album or track id and checks it's numeric.
+pub(crate) fn numeric_id(id: &str) -> ClientResult<&str> {
+    let trimmed = id.trim();
+    if !trimmed.is_empty() && trimmed.bytes().all(|byte| byte.is_ascii_digit()) {
+        Ok(trimmed)
+    } else {
+        Err(ClientError::InvalidId(id.to_owned()))
+    }
+}
+
+/// Trims a mix id and checks it's alphanumeric.
+pub(crate) fn mix_id(id: &str) -> ClientResult<&str> {
+    let trimmed = id.trim();
+    if !trimmed.is_empty() && trimmed.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+        Ok(trimmed)
+    } else {
+        Err(ClientError::InvalidId(id.to_owned()))
+    }
+}
+
+/// Trims a genre path and checks it only contains letters, digits, `-` and `_`.
+pub(crate) fn genre_path(path: &str) -> ClientResult<&str> {
+    let trimmed = path.trim();
+    let is_valid = !trimmed.is_empty()
+        && trimmed
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_');
+    if is_valid {
+        Ok(trimmed)
+    } else {
+        Err(ClientError::InvalidId(path.to_owned()))
+    }
+}
+
 #[derive(Default, Debug, Deserialize)]
 pub struct TidalItems<T> {
     pub items: Vec<T>,
+    #[serde(rename = "totalNumberOfItems")]
+    pub total_number_of_items: Option<u32>,
 }
 
 #[derive(Default, Debug, Deserialize)]
@@ -77,32 +320,480 @@ pub struct TidalSearch {
     pub albums: TidalItems<Album>,
     pub playlists: TidalItems<Playlist>,
     pub tracks: TidalItems<Track>,
+    /// People credited on the matches, only sent with `SearchOptions::include_contributors`.
+    #[serde(default)]
+    pub contributors: TidalItems<Contributor>,
+    /// The single best match across all types, see `Search::top_hit` to only request it.
+    #[serde(rename = "topHit", default, deserialize_with = "lenient_top_hit")]
+    pub top_hit: Option<SearchItem>,
+}
+
+// A best match of a type without a `SearchItem` variant shouldn't fail the whole search
+fn lenient_top_hit<'de, D>(deserializer: D) -> Result<Option<SearchItem>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| serde_json::from_value(value).ok()))
+}
+
+impl TidalSearch {
+    pub fn artist_rows(&self) -> Vec<ArtistRow> {
+        self.artists.items.iter().map(Into::into).collect()
+    }
+
+    pub fn album_rows(&self) -> Vec<AlbumRow> {
+        self.albums.items.iter().map(Into::into).collect()
+    }
+
+    pub fn playlist_rows(&self) -> Vec<PlaylistRow> {
+        self.playlists.items.iter().map(Into::into).collect()
+    }
+
+    pub fn track_rows(&self) -> Vec<TrackRow> {
+        self.tracks.items.iter().map(Into::into).collect()
+    }
+}
+
+/// A single typed search result, e.g. the best match of a search.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum SearchItem {
+    #[serde(rename = "ARTISTS")]
+    Artist(Artist),
+    #[serde(rename = "ALBUMS")]
+    Album(Album),
+    #[serde(rename = "PLAYLISTS")]
+    Playlist(Playlist),
+    #[serde(rename = "TRACKS")]
+    Track(Track),
+    #[serde(rename = "VIDEOS")]
+    Video(Video),
+}
+
+/// What a Tidal link points to, see `Tidal::resolve_url`.
+#[derive(Debug)]
+pub enum Resource {
+    Track(Track),
+    Album(Album),
+    Artist(Artist),
+    Playlist(Playlist),
 }
 
 // Tidal API
 
+// Least recently used search responses, entries expire `ttl` after they were stored
+struct SearchCache {
+    capacity: usize,
+    ttl: Duration,
+    // The most recently used entry is at the back
+    entries: Mutex<VecDeque<(String, Instant, String)>>,
+}
+
+impl SearchCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap();
+        let position = entries.iter().position(|(entry_key, _, _)| entry_key == key)?;
+        let entry = entries.remove(position)?;
+        if entry.1.elapsed() >= self.ttl {
+            return None;
+        }
+        let body = entry.2.clone();
+        entries.push_back(entry);
+        Some(body)
+    }
+
+    fn insert(&self, key: String, body: String) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(entry_key, _, _)| *entry_key != key);
+        entries.push_back((key, Instant::now(), body));
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+    }
+}
+
+// Position of `Tidal::paginate` in a paginated endpoint
+struct PageState<T> {
+    url: String,
+    offset: u32,
+    items: VecDeque<T>,
+    done: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionInfo {
+    country_code: String,
+}
+
+/// Client for the Tidal API.
+///
+/// Every `Tidal` owns its own connection pool, shared only with the views returned by
+/// `Tidal::with_country`. Reusing a single instance (e.g. behind an `Arc`) shares the pool
+/// between tasks, while creating a `Tidal` per request opens new connections every time.
+/// Dropping the last of them drops the pool, whose idle connections are then closed in the
+/// background.
 pub struct Tidal {
     client: Client,
-    pub(crate) credentials: TidalCredentials,
+    base_url: String,
+    default_limit: u16,
+    max_limit: u16,
+    // The session can be updated while the client is in use, e.g. when the country changes.
+    // The state behind an `Arc` is shared with the views of `with_country`.
+    pub(crate) credentials: Arc<RwLock<TidalCredentials>>,
+    max_concurrency: usize,
+    // Shared by all requests so they don't exceed `max_concurrency` together
+    request_permits: Arc<Semaphore>,
+    // Etag and body of previous GET responses keyed by request, only set when enabled
+    etag_cache: Option<Arc<Mutex<HashMap<String, (String, String)>>>>,
+    search_cache: Option<Arc<SearchCache>>,
+    max_retries: u32,
+    retry_backoff: Duration,
+    retry_policy: fn(StatusCode) -> bool,
+    token_header: bool,
+    // Sent instead of the session's country code, see `with_country`
+    country_override: Option<String>,
 }
 
-impl Tidal {
+/// Configuration of a `Tidal` client.
+///
+/// ```no_run
+/// # use rstidal::auth::TidalCredentials;
+/// # use rstidal::client::TidalBuilder;
+/// # fn run(credentials: TidalCredentials) {
+/// let client = TidalBuilder::new().max_concurrency(4).build(credentials).unwrap();
+/// # }
+/// ```
+pub struct TidalBuilder {
+    max_concurrency: usize,
+    etag_cache: bool,
+    timeout: Option<Duration>,
+    base_url: String,
+    default_limit: u16,
+    max_limit: u16,
+    user_agent: String,
+    proxy: Option<Proxy>,
+    accept_invalid_certs: bool,
+    search_cache: Option<(usize, Duration)>,
+    max_retries: u32,
+    retry_backoff: Duration,
+    retry_policy: fn(StatusCode) -> bool,
+    token_header: bool,
+}
+
+impl Default for TidalBuilder {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 8,
+            etag_cache: false,
+            timeout: None,
+            base_url: default_base_url(),
+            default_limit: DEFAULT_LIMIT,
+            max_limit: DEFAULT_MAX_LIMIT,
+            user_agent: DEFAULT_USER_AGENT.to_owned(),
+            proxy: None,
+            accept_invalid_certs: false,
+            search_cache: None,
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(500),
+            retry_policy: is_retryable,
+            token_header: true,
+        }
+    }
+}
+
+#[cfg(not(test))]
+fn default_base_url() -> String {
+    "https://api.tidalhifi.com/v1".to_owned()
+}
+
+#[cfg(test)]
+fn default_base_url() -> String {
+    mockito::server_url()
+}
+
+impl TidalBuilder {
     #[must_use]
-    pub fn new(credentials: TidalCredentials) -> Self {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of requests in flight at once, across all tasks using the client. Further
+    /// requests wait until one finishes, e.g. when fanning out with `Tracks::get_many`. Defaults
+    /// to 8, values lower than 1 are treated as 1.
+    #[must_use]
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Remembers the etag and body of GET responses. Repeated GETs send `If-None-Match` and
+    /// reuse the remembered body when Tidal answers `304 Not Modified`. Disabled by default.
+    #[must_use]
+    pub fn etag_cache(mut self, enabled: bool) -> Self {
+        self.etag_cache = enabled;
+        self
+    }
+
+    /// Timeout of every request, from connecting until the response body is read. Requests
+    /// don't time out by default.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// URL relative API paths are appended to, defaults to `https://api.tidalhifi.com/v1`.
+    #[must_use]
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_owned();
+        self
+    }
+
+    /// Number of items requested when no `limit` is passed to methods returning a page of
+    /// items, e.g. `Search::find`. Defaults to `DEFAULT_LIMIT`, values lower than 1 are treated
+    /// as 1.
+    #[must_use]
+    pub fn default_limit(mut self, limit: u16) -> Self {
+        self.default_limit = limit.max(1);
+        self
+    }
+
+    /// Largest `limit` sent to methods returning a page of items, larger limits are lowered to
+    /// it since Tidal rejects them. Defaults to `DEFAULT_MAX_LIMIT`, values lower than 1 are
+    /// treated as 1.
+    #[must_use]
+    pub fn max_limit(mut self, limit: u16) -> Self {
+        self.max_limit = limit.max(1);
+        self
+    }
+
+    /// `User-Agent` sent with every request, defaults to `DEFAULT_USER_AGENT`.
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_owned();
+        self
+    }
+
+    /// Keeps up to `capacity` search results for `ttl`, repeating a search within that time
+    /// doesn't make a request. Least recently used results are dropped first. Disabled by
+    /// default.
+    #[must_use]
+    pub fn search_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.search_cache = Some((capacity, ttl));
+        self
+    }
+
+    /// How often a request is repeated when `retry_policy` considers its response temporary.
+    /// Only `GET` and `HEAD` requests are retried, repeating others could apply a change twice.
+    /// Defaults to 2, 0 disables retries.
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Time waited before the first retry, doubled for every following one up to
+    /// `MAX_RETRY_BACKOFF`. Defaults to 500ms.
+    #[must_use]
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Decides from the status of a failed response whether the request is retried, defaults
+    /// to `is_retryable`.
+    #[must_use]
+    pub fn retry_policy(mut self, policy: fn(StatusCode) -> bool) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Sends the application token of the credentials as `X-Tidal-Token` header, which some
+    /// endpoints require. Enabled by default, when disabled the token is sent as `token` query
+    /// parameter instead.
+    #[must_use]
+    pub fn token_header(mut self, enabled: bool) -> Self {
+        self.token_header = enabled;
+        self
+    }
+
+    /// Sends all requests through `proxy`, e.g. a debug proxy like Charles or Fiddler.
+    #[must_use]
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Accepts any TLS certificate, which is needed when a debug proxy intercepts the traffic.
+    ///
+    /// **Never enable this outside of debugging.** Any certificate is trusted, including expired
+    /// ones and ones issued for other hosts, so anyone able to intercept the traffic can read
+    /// and modify it, including the session and the user's credentials.
+    #[must_use]
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Builds the client, fails with `ClientError::MissingSession` when no session was obtained
+    /// for `credentials`.
+    pub fn build(self, credentials: TidalCredentials) -> ClientResult<Tidal> {
         if credentials.session.is_none() {
-            panic!("A session needs to be obtatined before using Tidal");
+            return Err(ClientError::MissingSession);
         }
 
-        Self {
-            client: Client::new(),
-            credentials,
+        let client = Client::builder().user_agent(self.user_agent);
+        #[cfg(feature = "gzip")]
+        let client = client.gzip(true);
+        #[cfg(feature = "brotli")]
+        let client = client.brotli(true);
+        let client = match self.timeout {
+            Some(timeout) => client.timeout(timeout),
+            None => client,
+        };
+        let client = match self.proxy {
+            Some(proxy) => client.proxy(proxy),
+            None => client,
+        };
+        let client = client.danger_accept_invalid_certs(self.accept_invalid_certs);
+
+        Ok(Tidal {
+            client: client.build()?,
+            base_url: self.base_url,
+            default_limit: self.default_limit,
+            max_limit: self.max_limit,
+            credentials: Arc::new(RwLock::new(credentials)),
+            max_concurrency: self.max_concurrency,
+            request_permits: Arc::new(Semaphore::new(self.max_concurrency)),
+            etag_cache: if self.etag_cache {
+                Some(Arc::new(Mutex::new(HashMap::new())))
+            } else {
+                None
+            },
+            search_cache: self.search_cache.map(|(capacity, ttl)| {
+                Arc::new(SearchCache {
+                    capacity,
+                    ttl,
+                    entries: Mutex::new(VecDeque::with_capacity(capacity)),
+                })
+            }),
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+            retry_policy: self.retry_policy,
+            token_header: self.token_header,
+            country_override: None,
+        })
+    }
+}
+
+impl Tidal {
+    /// Client with the default configuration, see `TidalBuilder` to configure it.
+    ///
+    /// Panics when no session was obtained for `credentials`.
+    #[must_use]
+    pub fn new(credentials: TidalCredentials) -> Self {
+        match TidalBuilder::new().build(credentials) {
+            Ok(tidal) => tidal,
+            Err(err) => panic!("{}", err),
         }
     }
 
+    /// Runs `f` for every item with at most `max_concurrency` futures in flight, the requests
+    /// they make are limited by `api_call` as well. Results keep the order of `items`.
+    pub(crate) async fn fan_out<I, F, Fut, T>(&self, items: I, f: F) -> Vec<T>
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        stream::iter(items)
+            .map(f)
+            .buffered(self.max_concurrency)
+            .collect()
+            .await
+    }
+
+    /// Number of items requested when no `limit` is given, see `TidalBuilder::default_limit`.
+    pub fn default_limit(&self) -> u16 {
+        self.default_limit
+    }
+
+    /// Largest `limit` sent to endpoints, see `TidalBuilder::max_limit`.
+    pub fn max_limit(&self) -> u16 {
+        self.max_limit
+    }
+
     pub fn user_id(&self) -> u32 {
+        self.session().user_id
+    }
+
+    /// Country requests are made for, e.g. `US`. It's owned since the session can change while
+    /// the client is in use, see `refresh_country`. Views of `with_country` return their
+    /// override.
+    pub fn country_code(&self) -> String {
+        match &self.country_override {
+            Some(country_code) => country_code.clone(),
+            None => self.session().country_code,
+        }
+    }
+
+    /// Profile of the authenticated user.
+    pub async fn me(&self) -> ClientResult<User> {
+        let url = format!("/users/{}", self.user_id());
+        let result = self.get(&url, &mut HashMap::new()).await?;
+        Self::convert_result::<User>(&result)
+    }
+
+    /// A copy of the session currently in use.
+    pub fn session(&self) -> Session {
         // Here it's safe to use unwrap because in ::new() we already checked that there's a valid
         // session
-        self.credentials.session.as_ref().unwrap().user_id
+        self.credentials.read().unwrap().session.clone().unwrap()
+    }
+
+    /// Sends `country_code` instead of the session's country code on requests made through the
+    /// returned view, e.g. to check the availability of the catalog in another region. The view
+    /// offers every endpoint and shares the session, connection pool, caches and concurrency
+    /// limit with this client. The session itself is left untouched.
+    pub fn with_country(&self, country_code: &str) -> Tidal {
+        Tidal {
+            client: self.client.clone(),
+            base_url: self.base_url.clone(),
+            default_limit: self.default_limit,
+            max_limit: self.max_limit,
+            credentials: Arc::clone(&self.credentials),
+            max_concurrency: self.max_concurrency,
+            request_permits: Arc::clone(&self.request_permits),
+            etag_cache: self.etag_cache.clone(),
+            search_cache: self.search_cache.clone(),
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+            retry_policy: self.retry_policy,
+            token_header: self.token_header,
+            country_override: Some(country_code.to_owned()),
+        }
+    }
+
+    /// Reads the country of the session from Tidal and uses it for all following requests.
+    pub async fn refresh_country(&self) -> ClientResult<()> {
+        let result = self.get("/sessions", &mut HashMap::new()).await?;
+        let country_code = Self::convert_result::<SessionInfo>(&result)?.country_code;
+        if let Some(session) = self.credentials.write().unwrap().session.as_mut() {
+            session.country_code = country_code;
+        }
+        Ok(())
+    }
+
+    /// Checks whether Tidal still accepts the session, e.g. to ask for a new login before
+    /// showing any content. The session isn't renewed, even with `reauthenticate_with`.
+    pub async fn verify_session(&self) -> ClientResult<bool> {
+        match self.send_request(Method::GET, "/sessions", None, None, None).await {
+            Ok(_) => Ok(true),
+            Err(ClientError::Unauthorized) => Ok(false),
+            Err(err) => Err(err),
+        }
     }
 
     async fn api_call(
@@ -113,28 +804,91 @@ impl Tidal {
         payload: Option<&HashMap<&str, &str>>,
         etag: Option<String>,
     ) -> ClientResult<Response> {
-        #[cfg(not(test))]
-        let base_url: &str = "https://api.tidalhifi.com/v1";
-        #[cfg(test)]
-        let base_url: &str = &mockito::server_url();
+        let result = self.send_request(method.clone(), url, query, payload, etag.clone()).await;
+        if let Err(ClientError::Unauthorized) = result {
+            if self.reauthenticate().await {
+                return self.send_request(method, url, query, payload, etag).await;
+            }
+        }
+        result
+    }
+
+    // Logs in again when the credentials opted in with `reauthenticate_with`. Returns whether a
+    // new session is in use.
+    async fn reauthenticate(&self) -> bool {
+        let (token, login) = {
+            let credentials = self.credentials.read().unwrap();
+            (credentials.token.clone(), credentials.login.clone())
+        };
+        let (username, password) = match login {
+            Some(login) => login,
+            None => return false,
+        };
+
+        match Session::get_session(&token, &username, &password).await {
+            Ok(session) => {
+                debug!("session expired, logged in again as user {}", session.user_id);
+                self.credentials.write().unwrap().session = Some(session);
+                true
+            }
+            Err(err) => {
+                warn!("logging in again after the session expired failed: {}", err);
+                false
+            }
+        }
+    }
+
+    async fn send_request(
+        &self,
+        method: Method,
+        url: &str,
+        query: Option<&HashMap<String, String>>,
+        payload: Option<&HashMap<&str, &str>>,
+        etag: Option<String>,
+    ) -> ClientResult<Response> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "api_call",
+            method = %method,
+            path = %url,
+            status = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
 
         let mut url: Cow<str> = url.into();
         if !url.starts_with("http") {
-            url = [base_url, &url].concat().into();
+            url = [&self.base_url, &url].concat().into();
         }
 
-        let Session { session_id, country_code, .. } = self.credentials.session.as_ref().unwrap();
+        let Session { session_id, country_code, access_token, .. } = self.session();
+        let country_code = self.country_override.clone().unwrap_or(country_code);
+        let token = self.credentials.read().unwrap().token.clone();
 
         let mut headers = HeaderMap::new();
-        headers.insert("X-Tidal-SessionId", session_id.parse().unwrap());
+        // Sessions created with the device authorization flow use a bearer token instead
+        if let Some(access_token) = access_token {
+            headers.insert("Authorization", sensitive(&format!("Bearer {}", access_token)));
+        } else {
+            headers.insert("X-Tidal-SessionId", sensitive(&session_id));
+        }
         headers.insert("Origin", "http://listen.tidal.com".parse().unwrap());
         if let Some(etag) = etag {
             headers.insert("If-None-Match", etag.parse().unwrap());
         }
 
-        // Tidal's API requires countryCode to always be passed
+        // Tidal's API requires countryCode to always be passed, `query` may override it
         let mut query_params: HashMap<String, String> = HashMap::new();
-        query_params.insert("countryCode".to_owned(), country_code.to_owned());
+        query_params.insert("countryCode".to_owned(), country_code);
+        if !token.is_empty() {
+            if !self.token_header {
+                query_params.insert("token".to_owned(), token);
+            } else if let Ok(mut token) = HeaderValue::from_str(&token) {
+                token.set_sensitive(true);
+                headers.insert("X-Tidal-Token", token);
+            }
+        }
 
         if let Some(query) = query {
             for (key, value) in query.iter() {
@@ -142,11 +896,15 @@ impl Tidal {
             }
         }
 
-        let response = {
+        let url = url.into_owned();
+        // Repeating other requests could e.g. add the same tracks twice
+        let idempotent = method == Method::GET || method == Method::HEAD;
+        let mut retries = 0;
+        let response = loop {
             let builder = self
                 .client
-                .request(method, &url.into_owned())
-                .headers(headers)
+                .request(method.clone(), &url)
+                .headers(headers.clone())
                 .query(&query_params);
 
             // Only add payload when sent
@@ -156,12 +914,37 @@ impl Tidal {
                 builder
             };
 
-            debug!("request builder: {:?}", builder);
-            builder.send().await.map_err(ClientError::from)?
+            // Neither headers nor query are logged, they carry the session and the token
+            debug!("request: {} {}", method, url);
+            // The permit is only held while sending, not while waiting for a retry
+            let response = {
+                let _permit = self.request_permits.acquire().await;
+                builder.send().await.map_err(ClientError::from)?
+            };
+
+            let status = response.status();
+            if status.is_success()
+                || !idempotent
+                || retries >= self.max_retries
+                || !(self.retry_policy)(status)
+            {
+                break response;
+            }
+            let backoff = retry_delay(self.retry_backoff, retries);
+            retries += 1;
+            warn!("{} answered {}, retry {} in {:?}", url, status, retries, backoff);
+            tokio::time::delay_for(backoff).await;
         };
 
         debug!("response content: {:?}", response);
-        if response.status().is_success() {
+        #[cfg(feature = "tracing")]
+        {
+            span.record("status", &response.status().as_u16());
+            span.record("elapsed_ms", &(started.elapsed().as_millis() as u64));
+            tracing::debug!(parent: &span, "request finished");
+        }
+        // Only conditional requests are answered with 304, the caller knows what to reuse
+        if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
             Ok(response)
         } else {
             Err(ClientError::from_response(response).await)
@@ -176,11 +959,8 @@ impl Tidal {
             .headers()
             .clone();
 
-        if let Ok(etag) = headers
-            .get("etag")
-            .expect("etag header to be present")
-            .to_str()
-        {
+        // Proxies may drop the header, which mustn't bring down the caller
+        if let Ok(etag) = headers.get("etag").ok_or(ClientError::ParseEtag)?.to_str() {
             Ok(etag.to_owned())
         } else {
             Err(ClientError::ParseEtag)
@@ -192,49 +972,315 @@ impl Tidal {
         url: &str,
         params: &mut HashMap<String, String>,
     ) -> ClientResult<String> {
-        self.api_call(Method::GET, &url, Some(params), None, None)
-            .await?
-            .text()
-            .await
-            .map_err(Into::into)
+        let cache = match &self.etag_cache {
+            Some(cache) => cache,
+            None => {
+                return self
+                    .api_call(Method::GET, &url, Some(params), None, None)
+                    .await?
+                    .text()
+                    .await
+                    .map_err(Into::into)
+            }
+        };
+
+        let key = self.request_key(url, params);
+        let cached = cache.lock().unwrap().get(&key).cloned();
+        let etag = cached.as_ref().map(|(etag, _)| etag.clone());
+        let response = self.api_call(Method::GET, &url, Some(params), None, etag).await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return match cached {
+                Some((_, body)) => Ok(body),
+                None => Err(response.status().into()),
+            };
+        }
+        let etag = response
+            .headers()
+            .get("etag")
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_owned);
+        let body = response.text().await?;
+        if let Some(etag) = etag {
+            cache.lock().unwrap().insert(key, (etag, body.clone()));
+        }
+        Ok(body)
     }
 
-    pub async fn post(
+    /// Same as `get` for search endpoints, answered from the search cache when enabled with
+    /// `TidalBuilder::search_cache`.
+    pub(crate) async fn get_search(
         &self,
         url: &str,
-        payload: &HashMap<&str, &str>,
-        etag: Option<String>,
+        params: &mut HashMap<String, String>,
     ) -> ClientResult<String> {
-        self.api_call(Method::POST, &url, None, Some(payload), etag)
-            .await?
-            .text()
-            .await
-            .map_err(Into::into)
+        let cache = match &self.search_cache {
+            Some(cache) => cache,
+            None => return self.get(url, params).await,
+        };
+
+        let key = self.request_key(url, params);
+        if let Some(body) = cache.get(&key) {
+            debug!("search served from cache: {}", key);
+            return Ok(body);
+        }
+        let body = self.get(url, params).await?;
+        cache.insert(key, body.clone());
+        Ok(body)
     }
 
-    pub async fn put(
+    /// GET sending `etag` as `If-None-Match`. Returns `None` when Tidal answers `304 Not
+    /// Modified`, otherwise the body together with the etag of the response.
+    pub async fn get_if_modified(
         &self,
         url: &str,
-        payload: &HashMap<&str, &str>,
-        etag: String,
-    ) -> ClientResult<String> {
-        self.api_call(Method::PUT, url, None, Some(payload), Some(etag))
-            .await?
-            .text()
-            .await
-            .map_err(Into::into)
-    }
+        params: &mut HashMap<String, String>,
+        etag: Option<String>,
+    ) -> ClientResult<Option<(String, Option<String>)>> {
+        let response = self.api_call(Method::GET, &url, Some(params), None, etag).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
 
-    // The following functions are for backward compatibility only
-    //
-    pub async fn search(&self, term: &str, limit: Option<u16>) -> ClientResult<TidalSearch> {
-        warn!("DEPRECATION WARNING!: This method will be deprecated in the next version. Please favor using .searches().find()");
-        self.searches().find(term, limit).await
+        let etag = response
+            .headers()
+            .get("etag")
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_owned);
+        let body = response.text().await?;
+        Ok(Some((body, etag)))
     }
 
-    pub async fn artist(&self, id: &str) -> ClientResult<Artist> {
-        warn!("DEPRECATION WARNING!: This method will be deprecated in the next version. Please favor using .artists().get()");
-        self.artists().get(id).await
+    // The same URL returns different content per country and query
+    pub(crate) fn request_key(&self, url: &str, params: &HashMap<String, String>) -> String {
+        let mut params: Vec<_> = params.iter().collect();
+        params.sort();
+        let query: Vec<String> = params
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        format!("{}:{}?{}", self.country_code(), url, query.join("&"))
+    }
+
+    /// Fetches every page of a paginated endpoint, `page_size` items at a time. A `page_size`
+    /// above `max_limit` is lowered to it.
+    pub async fn get_all<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        page_size: u16,
+    ) -> ClientResult<Vec<T>> {
+        let page_size = self.page_size(page_size);
+        let mut items: Vec<T> = Vec::new();
+        loop {
+            let page = self.get_page::<T>(url, page_size, items.len() as u32).await?;
+            let page_len = page.items.len();
+            items.extend(page.items);
+
+            let reached_total = page
+                .total_number_of_items
+                .map_or(false, |total| items.len() >= total as usize);
+            if page_len < page_size as usize || reached_total {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Lazily iterates over every item of a paginated endpoint. A page of `page_size` items is
+    /// only fetched once the items of the previous page are consumed. The stream ends after the
+    /// last page or the first error. A `page_size` above `max_limit` is lowered to it.
+    pub fn paginate<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        url: &str,
+        page_size: u16,
+    ) -> impl Stream<Item = ClientResult<T>> + 'a {
+        let page_size = self.page_size(page_size);
+        let state = PageState {
+            url: url.to_owned(),
+            offset: 0,
+            items: VecDeque::new(),
+            done: false,
+        };
+
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(item) = state.items.pop_front() {
+                    return Some((Ok(item), state));
+                }
+                if state.done {
+                    return None;
+                }
+
+                let page = match self.get_page::<T>(&state.url, page_size, state.offset).await {
+                    Ok(page) => page,
+                    Err(err) => {
+                        state.done = true;
+                        return Some((Err(err), state));
+                    }
+                };
+                let page_len = page.items.len();
+                state.offset += page_len as u32;
+                state.done = page_len < page_size as usize
+                    || page
+                        .total_number_of_items
+                        .map_or(false, |total| state.offset >= total);
+                state.items.extend(page.items);
+            }
+        })
+    }
+
+    // Tidal answers a larger page size with fewer items, which would look like the last page
+    fn page_size(&self, page_size: u16) -> u16 {
+        if page_size > self.max_limit {
+            warn!("page size {} is above the maximum, using {} instead", page_size, self.max_limit);
+            return self.max_limit;
+        }
+        page_size
+    }
+
+    async fn get_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        page_size: u16,
+        offset: u32,
+    ) -> ClientResult<TidalItems<T>> {
+        let mut params =
+            pagination_params(Some(page_size), Some(offset), page_size, self.max_limit)?;
+        let result = self.get(url, &mut params).await?;
+        Self::convert_result::<TidalItems<T>>(&result)
+    }
+
+    pub async fn post(
+        &self,
+        url: &str,
+        payload: &HashMap<&str, &str>,
+        etag: Option<String>,
+    ) -> ClientResult<String> {
+        self.api_call(Method::POST, &url, None, Some(payload), etag)
+            .await?
+            .text()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Same as `post`, also returning the etag Tidal answered with, if any.
+    pub(crate) async fn post_with_etag(
+        &self,
+        url: &str,
+        payload: &HashMap<&str, &str>,
+        etag: Option<String>,
+    ) -> ClientResult<(String, Option<String>)> {
+        let response = self.api_call(Method::POST, &url, None, Some(payload), etag).await?;
+        let etag = response
+            .headers()
+            .get("etag")
+            .and_then(|etag| etag.to_str().ok())
+            .map(ToOwned::to_owned);
+        Ok((response.text().await?, etag))
+    }
+
+    pub async fn put(
+        &self,
+        url: &str,
+        payload: &HashMap<&str, &str>,
+        etag: String,
+    ) -> ClientResult<String> {
+        self.api_call(Method::PUT, url, None, Some(payload), Some(etag))
+            .await?
+            .text()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn delete(&self, url: &str, etag: Option<String>) -> ClientResult<String> {
+        self.api_call(Method::DELETE, url, None, None, etag)
+            .await?
+            .text()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Fetches what a Tidal link points to, e.g. `https://tidal.com/browse/track/79914999`.
+    /// Links of the web player (`listen.tidal.com`) and links without `/browse` work as well,
+    /// anything else fails with `ClientError::UnsupportedUrl`.
+    pub async fn resolve_url(&self, url: &str) -> ClientResult<Resource> {
+        let unsupported = || ClientError::UnsupportedUrl(url.to_owned());
+        let trimmed = url.trim();
+        let with_scheme = if trimmed.contains("://") {
+            trimmed.to_owned()
+        } else {
+            format!("https://{}", trimmed)
+        };
+        let parsed = Url::parse(&with_scheme).map_err(|_| unsupported())?;
+
+        if !matches!(
+            parsed.host_str(),
+            Some("tidal.com") | Some("www.tidal.com") | Some("listen.tidal.com")
+        ) {
+            return Err(unsupported());
+        }
+        let mut segments = parsed
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .filter(|segment| !segment.is_empty());
+        let mut kind = segments.next();
+        if kind == Some("browse") {
+            kind = segments.next();
+        }
+        let id = segments.next().ok_or_else(unsupported)?;
+
+        match kind {
+            Some("track") => Ok(Resource::Track(self.tracks().get(id).await?)),
+            Some("album") => Ok(Resource::Album(self.albums().get(id).await?)),
+            Some("artist") => Ok(Resource::Artist(self.artists().get(id).await?)),
+            Some("playlist") => Ok(Resource::Playlist(self.playlists().get(id).await?)),
+            _ => Err(unsupported()),
+        }
+    }
+
+    /// Downloads an image, e.g. from `Album::cover_url`, through the configured client (proxy,
+    /// timeout, user agent). Images are public, so no session is sent. Downloads count towards
+    /// `TidalBuilder::max_concurrency` like every other request.
+    pub async fn fetch_image(&self, url: &str) -> ClientResult<Bytes> {
+        let _permit = self.request_permits.acquire().await;
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(ClientError::from_response(response).await);
+        }
+        Ok(response.bytes().await?)
+    }
+
+    /// Calls an endpoint this crate doesn't cover yet, with the same authentication, country code
+    /// and error handling as every other request. An empty response is returned as
+    /// `serde_json::Value::Null`.
+    pub async fn raw(
+        &self,
+        method: Method,
+        path: &str,
+        query: HashMap<String, String>,
+        body: Option<HashMap<&str, &str>>,
+    ) -> ClientResult<serde_json::Value> {
+        let result = self
+            .api_call(method, path, Some(&query), body.as_ref(), None)
+            .await?
+            .text()
+            .await?;
+        if result.trim().is_empty() {
+            return Ok(serde_json::Value::Null);
+        }
+        Self::convert_result::<serde_json::Value>(&result)
+    }
+
+    // The following functions are for backward compatibility only
+    //
+    pub async fn search(&self, term: &str, limit: Option<u16>) -> ClientResult<TidalSearch> {
+        warn!("DEPRECATION WARNING!: This method will be deprecated in the next version. Please favor using .searches().find()");
+        self.searches().find(term, limit, None).await
+    }
+
+    pub async fn artist(&self, id: &str) -> ClientResult<Artist> {
+        warn!("DEPRECATION WARNING!: This method will be deprecated in the next version. Please favor using .artists().get()");
+        self.artists().get(id).await
     }
 
     pub async fn search_artist(&self, term: &str, limit: Option<u16>) -> ClientResult<Vec<Artist>> {
@@ -297,9 +1343,21 @@ impl Tidal {
         self.playlists().create(title, description).await
     }
 
+    /// Parses a response body. `T` may borrow from `input`, e.g. `TrackLite`, which avoids
+    /// allocating its strings.
     pub fn convert_result<'a, T: Deserialize<'a>>(input: &'a str) -> ClientResult<T> {
         serde_json::from_str::<T>(input).map_err(Into::into)
     }
+
+    /// Same as `convert_result`, also returning the whole body as JSON, including the fields
+    /// `T` doesn't model.
+    pub fn convert_result_raw<T: DeserializeOwned>(
+        input: &str,
+    ) -> ClientResult<(T, serde_json::Value)> {
+        let raw = serde_json::from_str::<serde_json::Value>(input)?;
+        let result = T::deserialize(&raw)?;
+        Ok((result, raw))
+    }
 }
 
 #[cfg(test)]
@@ -307,6 +1365,7 @@ pub mod tests {
     use super::*;
     use crate::auth::Session;
     use mockito::{mock, Matcher};
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[tokio::test]
     async fn client_get() {
@@ -371,12 +1430,12 @@ pub mod tests {
     async fn client_search_artist() {
         let _mock = mock_request_success_from_file(
             "GET",
-            "/search",
+            "/search/artists",
             vec![
                 Matcher::UrlEncoded("countryCode".into(), "US".into()),
                 Matcher::UrlEncoded("query".into(), "trivium".into()),
             ],
-            "tests/files/search.json",
+            "tests/files/search_artists.json",
         )
         .create();
 
@@ -427,12 +1486,12 @@ pub mod tests {
     async fn client_search_album() {
         let _mock = mock_request_success_from_file(
             "GET",
-            "/search",
+            "/search/albums",
             vec![
                 Matcher::UrlEncoded("countryCode".into(), "US".into()),
                 Matcher::UrlEncoded("query".into(), "trivium".into()),
             ],
-            "tests/files/search.json",
+            "tests/files/search_albums.json",
         )
         .create();
 
@@ -462,12 +1521,12 @@ pub mod tests {
     async fn client_search_tracks() {
         let _mock = mock_request_success_from_file(
             "GET",
-            "/search",
+            "/search/tracks",
             vec![
                 Matcher::UrlEncoded("countryCode".into(), "US".into()),
                 Matcher::UrlEncoded("query".into(), "trivium".into()),
             ],
-            "tests/files/search.json",
+            "tests/files/search_tracks.json",
         )
         .create();
 
@@ -581,6 +1640,761 @@ pub mod tests {
         mock_update_playlist.assert();
     }
 
+    #[tokio::test]
+    async fn client_http_error_keeps_body() {
+        let _mock = mock("GET", "/")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(500)
+            .with_header("x-tidal-request-id", "request-id-1")
+            .with_body("<html><body>Internal Server Error</body></html>")
+            .create();
+
+        let client = TidalBuilder::new().max_retries(0).build(credential()).unwrap();
+        let result = client.get("/", &mut HashMap::new()).await;
+        match result {
+            Err(ClientError::Http { status, headers, body }) => {
+                assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+                assert_eq!(headers.get("x-tidal-request-id").unwrap(), "request-id-1");
+                assert_eq!(body, "<html><body>Internal Server Error</body></html>");
+            }
+            _ => panic!("expected an http error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+    }
+
+    #[tokio::test]
+    async fn client_retries_temporary_errors() {
+        let mock_unavailable = mock("GET", "/albums/79914994")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(503)
+            .create();
+        let mock_available = mock("GET", "/albums/79914994")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(200)
+            .with_body(r#"{"id": 79914994}"#)
+            .create();
+
+        let client = TidalBuilder::new()
+            .retry_backoff(Duration::from_millis(1))
+            .build(credential())
+            .unwrap();
+        let result = client.get("/albums/79914994", &mut HashMap::new()).await;
+
+        assert_eq!(result.unwrap(), r#"{"id": 79914994}"#);
+        mock_unavailable.assert();
+        mock_available.assert();
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        let backoff = Duration::from_millis(500);
+        assert_eq!(retry_delay(backoff, 0), backoff);
+        assert_eq!(retry_delay(backoff, 2), Duration::from_secs(2));
+        assert_eq!(retry_delay(backoff, 10), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_delay(backoff, 40), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_delay(Duration::from_secs(u64::MAX), 1), MAX_RETRY_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn client_does_not_retry_non_idempotent_requests() {
+        let mock = mock("POST", "/playlists/79914981")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(503)
+            .expect(1)
+            .create();
+
+        let client = TidalBuilder::new()
+            .retry_backoff(Duration::from_millis(1))
+            .build(credential())
+            .unwrap();
+        let result = client.post("/playlists/79914981", &HashMap::new(), None).await;
+
+        assert!(result.is_err());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn client_does_not_retry_client_errors() {
+        let mock = mock("GET", "/albums/79914993")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(404)
+            .with_body(r#"{"status": 404, "subStatus": 2001, "userMessage": "Not found"}"#)
+            .expect(1)
+            .create();
+
+        let client = TidalBuilder::new()
+            .retry_backoff(Duration::from_millis(1))
+            .build(credential())
+            .unwrap();
+        let result = client.get("/albums/79914993", &mut HashMap::new()).await;
+
+        assert!(result.unwrap_err().is_not_found());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn client_api_error() {
+        let _mock = mock("GET", "/")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(404)
+            .with_body(r#"{"status": 404, "subStatus": 2001, "userMessage": "Not found"}"#)
+            .create();
+
+        let result = client().get("/", &mut HashMap::new()).await;
+        match result {
+            Err(ClientError::Api(api_error)) => {
+                assert!(matches!(api_error, ApiError::Regular { status: 404, .. }));
+                assert_eq!(api_error.code(), TidalErrorCode::NotFound);
+            }
+            _ => panic!("expected an api error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn api_error_codes() {
+        let code = |status, sub_status| {
+            ApiError::Regular { status, sub_status, message: String::new() }.code()
+        };
+
+        assert_eq!(code(404, Some(2001)), TidalErrorCode::NotFound);
+        assert_eq!(code(401, Some(6001)), TidalErrorCode::SessionExpired);
+        assert_eq!(code(401, Some(11003)), TidalErrorCode::SessionExpired);
+        assert_eq!(code(401, Some(11002)), TidalErrorCode::Unauthorized);
+        assert_eq!(code(403, Some(5003)), TidalErrorCode::GeoRestricted);
+        assert_eq!(code(429, None), TidalErrorCode::RateLimited);
+        assert_eq!(code(400, Some(1002)), TidalErrorCode::Unknown(400));
+    }
+
+    #[tokio::test]
+    async fn client_me() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/users/1234",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/user.json",
+        );
+
+        let result: User = client().me().await.unwrap();
+        assert_eq!(result.id, Some(1234));
+        assert_eq!(result.first_name, Some("Matt".to_owned()));
+        assert_eq!(result.last_name, Some("Heafy".to_owned()));
+        assert_eq!(result.email, Some("myuser@example.com".to_owned()));
+        assert_eq!(result.country_code, Some("US".to_owned()));
+        assert_eq!(result.created, Some("2020-03-14T17:42:10.155+0000".to_owned()));
+    }
+
+    #[test]
+    fn client_session_accessors() {
+        let client = client();
+        assert_eq!(client.country_code(), "US");
+        assert_eq!(client.user_id(), 1234);
+        assert_eq!(client.session().session_id, "session-id-1");
+    }
+
+    #[tokio::test]
+    async fn client_refresh_country() {
+        let _mock = mock_request_success(
+            "GET",
+            "/sessions",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            r#"{"sessionId": "session-id-1", "userId": 1234, "countryCode": "DE"}"#,
+        );
+
+        let client = client();
+        client.refresh_country().await.unwrap();
+        assert_eq!(client.session().country_code, "DE");
+    }
+
+    #[tokio::test]
+    async fn client_verify_session() {
+        let _mock = mock_request_success(
+            "GET",
+            "/sessions",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            r#"{"sessionId": "session-id-1", "userId": 1234, "countryCode": "US"}"#,
+        );
+
+        assert!(client().verify_session().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn client_verify_expired_session() {
+        let _mock = mock("GET", "/sessions")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(401)
+            .with_body(r#"{"status": 401, "subStatus": 11003, "userMessage": "Session expired"}"#)
+            .create();
+
+        assert!(!client().verify_session().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn client_reauthenticates_expired_session() {
+        let mock_expired = mock("GET", "/albums/79914996")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("x-tidal-sessionid", "session-id-1")
+            .with_status(401)
+            .with_body(r#"{"status": 401, "subStatus": 11003, "userMessage": "Session expired"}"#)
+            .expect(1)
+            .create();
+        let mock_login = mock("POST", "/")
+            .match_query(Matcher::UrlEncoded("token".into(), "some_token".into()))
+            .match_body(Matcher::Regex("username=reauth".to_owned()))
+            .with_status(200)
+            .with_body(r#"{"userId": 1234, "sessionId": "session-id-2", "countryCode": "US"}"#)
+            .expect(1)
+            .create();
+        let mock_retried = mock("GET", "/albums/79914996")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("x-tidal-sessionid", "session-id-2")
+            .with_status(200)
+            .with_body(r#"{"id": 79914996}"#)
+            .expect(1)
+            .create();
+
+        let credentials = credential().reauthenticate_with("reauth@example.com", "password");
+        let client = Tidal::new(credentials);
+        let response = client.get("/albums/79914996", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"id": 79914996}"#);
+        assert_eq!(client.session().session_id, "session-id-2");
+        mock_expired.assert();
+        mock_login.assert();
+        mock_retried.assert();
+    }
+
+    #[tokio::test]
+    async fn client_unauthorized_without_opt_in() {
+        let _mock = mock("GET", "/albums/79914997")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(401)
+            .create();
+
+        let result = client().get("/albums/79914997", &mut HashMap::new()).await;
+        assert!(matches!(result, Err(ClientError::Unauthorized)));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn client_accepts_gzip() {
+        let _mock = mock("GET", "/albums/79914994")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("accept-encoding", Matcher::Regex("gzip".to_owned()))
+            .with_status(200)
+            .with_body(r#"{"result": "ok"}"#)
+            .create();
+
+        let response = client().get("/albums/79914994", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "ok"}"#);
+    }
+
+    #[cfg(feature = "brotli")]
+    #[tokio::test]
+    async fn client_accepts_brotli() {
+        let _mock = mock("GET", "/albums/79914993")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("accept-encoding", Matcher::Regex("br".to_owned()))
+            .with_status(200)
+            .with_body(r#"{"result": "ok"}"#)
+            .create();
+
+        let response = client().get("/albums/79914993", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "ok"}"#);
+    }
+
+    // Keeps every log record, of all tests running concurrently
+    struct CapturingLogger(Mutex<Vec<String>>);
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+
+    #[tokio::test]
+    async fn client_logs_no_secrets() {
+        let _mock = mock_request_success(
+            "GET",
+            "/albums/79914980",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            r#"{"result": "ok"}"#,
+        );
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let mut credentials = credential();
+        if let Some(session) = credentials.session.as_mut() {
+            session.access_token = Some("access-token-1".to_owned());
+        }
+        let client = TidalBuilder::new().build(credentials).unwrap();
+        client.get("/albums/79914980", &mut HashMap::new()).await.unwrap();
+
+        let logs = LOGGER.0.lock().unwrap();
+        assert!(logs.iter().any(|line| line.contains("/albums/79914980")));
+        for secret in &["some_token", "session-id-1", "access-token-1"] {
+            assert!(logs.iter().all(|line| !line.contains(secret)), "{} was logged", secret);
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn client_traces_request() {
+        let _mock = mock_request_success(
+            "GET",
+            "/albums/79914995",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            r#"{"result": "ok"}"#,
+        );
+
+        client().get("/albums/79914995", &mut HashMap::new()).await.unwrap();
+        assert!(logs_contain("path=/albums/79914995"));
+        assert!(logs_contain("status=200"));
+        assert!(logs_contain("elapsed_ms="));
+    }
+
+    #[tokio::test]
+    async fn client_etag_cache() {
+        let mock_first = mock("GET", "/albums/79914992")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"1234\"")
+            .with_body(r#"{"id": 79914992}"#)
+            .expect(1)
+            .create();
+        let mock_not_modified = mock("GET", "/albums/79914992")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", "\"1234\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+
+        let client = TidalBuilder::new().etag_cache(true).build(credential()).unwrap();
+        let first = client.get("/albums/79914992", &mut HashMap::new()).await.unwrap();
+        let second = client.get("/albums/79914992", &mut HashMap::new()).await.unwrap();
+        assert_eq!(first, r#"{"id": 79914992}"#);
+        assert_eq!(second, first);
+        mock_first.assert();
+        mock_not_modified.assert();
+    }
+
+    #[test]
+    fn client_playlist_id() {
+        assert_eq!(
+            playlist_id(" 7ce7df87-6d37-4465-80db-84535a4e44a4\n").unwrap(),
+            "7ce7df87-6d37-4465-80db-84535a4e44a4"
+        );
+        assert!(matches!(
+            playlist_id("7ce7df87-6d37-4465-80db-84535a4e44a"),
+            Err(ClientError::InvalidId(_))
+        ));
+        assert!(matches!(
+            playlist_id("7ce7df87-6d37-4465-80db-84535a4e44az"),
+            Err(ClientError::InvalidId(_))
+        ));
+    }
+
+    #[test]
+    fn client_numeric_id() {
+        assert_eq!(numeric_id(" 37312 ").unwrap(), "37312");
+        assert!(matches!(numeric_id("trivium"), Err(ClientError::InvalidId(_))));
+        assert!(matches!(numeric_id(""), Err(ClientError::InvalidId(_))));
+    }
+
+    #[tokio::test]
+    async fn client_builder() {
+        let _mock = mock_request_success(
+            "GET",
+            "/v1/albums/79914991",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            r#"{"result": "ok"}"#,
+        );
+
+        let client = TidalBuilder::new()
+            .timeout(Duration::from_secs(5))
+            .base_url(&format!("{}/v1/", mockito::server_url()))
+            .build(credential())
+            .unwrap();
+        let response = client.get("/albums/79914991", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "ok"}"#);
+    }
+
+    #[tokio::test]
+    async fn client_user_agent() {
+        let _mock_default = mock("GET", "/albums/79914990")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("user-agent", DEFAULT_USER_AGENT)
+            .with_status(200)
+            .with_body(r#"{"result": "default"}"#)
+            .create();
+        let _mock_custom = mock("GET", "/albums/79914989")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("user-agent", "MyPlayer/2.0")
+            .with_status(200)
+            .with_body(r#"{"result": "custom"}"#)
+            .create();
+
+        let response = client().get("/albums/79914990", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "default"}"#);
+
+        let client = TidalBuilder::new().user_agent("MyPlayer/2.0").build(credential()).unwrap();
+        let response = client.get("/albums/79914989", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "custom"}"#);
+    }
+
+    #[tokio::test]
+    async fn client_resolve_url() {
+        let _mock_track = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/track.json",
+        );
+        let _mock_album = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album.json",
+        );
+        let _mock_artist = mock_request_success_from_file(
+            "GET",
+            "/artists/37312",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/artist.json",
+        );
+        let _mock_playlist = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/playlist.json",
+        );
+
+        let client = client();
+        let track = client.resolve_url("https://tidal.com/browse/track/79914999").await;
+        assert!(matches!(track, Ok(Resource::Track(Track { id: Some(79914999), .. }))));
+        let track = client.resolve_url("https://tidal.com/track/79914999/u").await;
+        assert!(matches!(track, Ok(Resource::Track(_))));
+        let album = client.resolve_url("https://listen.tidal.com/album/79914998").await;
+        assert!(matches!(album, Ok(Resource::Album(Album { id: Some(79914998), .. }))));
+        let artist = client.resolve_url("tidal.com/browse/artist/37312").await;
+        assert!(matches!(artist, Ok(Resource::Artist(Artist { id: Some(37312), .. }))));
+        let playlist = client
+            .resolve_url("https://listen.tidal.com/playlist/7ce7df87-6d37-4465-80db-84535a4e44a4")
+            .await;
+        assert!(matches!(playlist, Ok(Resource::Playlist(_))));
+    }
+
+    #[tokio::test]
+    async fn client_resolve_unsupported_url() {
+        let client = client();
+        for url in &[
+            "https://example.com/track/79914999",
+            "https://tidal.com/browse/video/79914999",
+            "https://tidal.com/browse/track",
+            "https://tidal.com/",
+            "",
+        ] {
+            let result = client.resolve_url(url).await;
+            assert!(matches!(result, Err(ClientError::UnsupportedUrl(_))), "{}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_etag_missing() {
+        let _mock = mock("GET", "/playlists/79914979")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_body("")
+            .create();
+
+        let result = client().etag("/playlists/79914979").await;
+        assert!(matches!(result, Err(ClientError::ParseEtag)));
+    }
+
+    #[tokio::test]
+    async fn client_fetch_image() {
+        let image: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, 0x4a, 0x46, 0x49, 0x46];
+        let _mock = mock("GET", "/images/404d8a74/e8d5/640x640.jpg")
+            .match_header("x-tidal-sessionid", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "image/jpeg")
+            .with_body(image)
+            .create();
+
+        let url = format!("{}/images/404d8a74/e8d5/640x640.jpg", mockito::server_url());
+        let result = client().fetch_image(&url).await.unwrap();
+        assert_eq!(&result[..], image);
+    }
+
+    #[tokio::test]
+    async fn client_fetch_missing_image() {
+        let _mock = mock("GET", "/images/404d8a74/e8d6/640x640.jpg")
+            .with_status(404)
+            .with_body("Not Found")
+            .create();
+
+        let url = format!("{}/images/404d8a74/e8d6/640x640.jpg", mockito::server_url());
+        let result = client().fetch_image(&url).await;
+        assert!(result.unwrap_err().is_not_found());
+    }
+
+    #[tokio::test]
+    async fn client_raw() {
+        let _mock = mock("POST", "/some/unmodelled/endpoint")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("filter".into(), "all".into()),
+            ]))
+            .match_header("x-tidal-sessionid", "session-id-1")
+            .match_body(Matcher::UrlEncoded("name".into(), "value".into()))
+            .with_status(200)
+            .with_body(r#"{"data": {"count": 3}}"#)
+            .create();
+
+        let mut query: HashMap<String, String> = HashMap::new();
+        query.insert("filter".to_owned(), "all".to_owned());
+        let mut body: HashMap<&str, &str> = HashMap::new();
+        body.insert("name", "value");
+        let result = client()
+            .raw(Method::POST, "/some/unmodelled/endpoint", query, Some(body))
+            .await
+            .unwrap();
+
+        assert_eq!(result["data"]["count"], 3);
+    }
+
+    #[tokio::test]
+    async fn client_token_header() {
+        let _mock_header = mock("GET", "/albums/79914988")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("x-tidal-token", "some_token")
+            .with_status(200)
+            .with_body(r#"{"result": "header"}"#)
+            .create();
+        let _mock_query = mock("GET", "/albums/79914987")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("token".into(), "some_token".into()),
+            ]))
+            .match_header("x-tidal-token", Matcher::Missing)
+            .with_status(200)
+            .with_body(r#"{"result": "query"}"#)
+            .create();
+
+        let response = client().get("/albums/79914988", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "header"}"#);
+
+        let client = TidalBuilder::new().token_header(false).build(credential()).unwrap();
+        let response = client.get("/albums/79914987", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "query"}"#);
+    }
+
+    #[test]
+    fn client_builder_proxy() {
+        let result = TidalBuilder::new()
+            .proxy(Proxy::all("http://127.0.0.1:8888").unwrap())
+            .danger_accept_invalid_certs(true)
+            .build(credential());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn client_builder_without_session() {
+        let result = TidalBuilder::new().build(TidalCredentials::new("some_token"));
+        assert!(matches!(result, Err(ClientError::MissingSession)));
+    }
+
+    #[tokio::test]
+    async fn client_with_country() {
+        let _mock = mock_request_success(
+            "GET",
+            "/albums/79914998",
+            vec![Matcher::UrlEncoded("countryCode".into(), "DE".into())],
+            r#"{"result": "ok"}"#,
+        );
+
+        let _mock_album = mock_request_success(
+            "GET",
+            "/albums/79914978",
+            vec![Matcher::UrlEncoded("countryCode".into(), "DE".into())],
+            r#"{"id": 79914978, "title": "In Waves"}"#,
+        );
+
+        let client = client();
+        let germany = client.with_country("DE");
+        let response = germany.get("/albums/79914998", &mut HashMap::new()).await.unwrap();
+        assert_eq!(response, r#"{"result": "ok"}"#);
+        let album = germany.albums().get("79914978").await.unwrap();
+        assert_eq!(album.id, Some(79914978));
+        assert_eq!(germany.country_code(), "DE");
+        assert_eq!(client.country_code(), "US");
+        assert_eq!(client.session().country_code, "US");
+    }
+
+    #[tokio::test]
+    async fn client_fan_out_max_concurrency() {
+        let client = TidalBuilder::new().max_concurrency(1).build(credential()).unwrap();
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        let results = client
+            .fan_out(0..4, |i| {
+                let in_flight = &in_flight;
+                let max_in_flight = &max_in_flight;
+                async move {
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::delay_for(Duration::from_millis(10)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    i
+                }
+            })
+            .await;
+
+        assert_eq!(results, vec![0, 1, 2, 3]);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn client_max_concurrency() {
+        let mock = mock("GET", "/albums/79914986")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(200)
+            .with_body(r#"{"result": "ok"}"#)
+            .expect(20)
+            .create();
+
+        let client = TidalBuilder::new().max_concurrency(2).build(credential()).unwrap();
+        let requests = (0..20).map(|_| async {
+            client.get("/albums/79914986", &mut HashMap::new()).await
+        });
+        let results = futures_util::future::join_all(requests).await;
+
+        assert!(results.iter().all(|result| result.as_ref().unwrap() == r#"{"result": "ok"}"#));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn client_get_all() {
+        let _mock_page_1 = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "50".into()),
+                Matcher::UrlEncoded("offset".into(), "0".into()),
+            ],
+            "tests/files/playlist_tracks_page_1.json",
+        );
+        let _mock_page_2 = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "50".into()),
+                Matcher::UrlEncoded("offset".into(), "50".into()),
+            ],
+            "tests/files/playlist_tracks_page_2.json",
+        );
+
+        let result: Vec<Track> = client()
+            .get_all("/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks", 50)
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 62);
+        assert_eq!(result[61].title, Some("Track 62".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn client_paginate() {
+        let _mock_page_1 = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks",
+            vec![Matcher::UrlEncoded("offset".into(), "0".into())],
+            "tests/files/playlist_tracks_page_1.json",
+        );
+        let _mock_page_2 = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks",
+            vec![Matcher::UrlEncoded("offset".into(), "50".into())],
+            "tests/files/playlist_tracks_page_2.json",
+        );
+
+        let client = client();
+        let mut tracks = Box::pin(
+            client.paginate::<Track>("/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks", 50),
+        );
+        let mut count = 0;
+        while let Some(track) = tracks.next().await {
+            track.unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 62);
+    }
+
+    #[tokio::test]
+    async fn client_get_all_above_max_limit() {
+        let url = "/playlists/3f1c2b6e-0d4a-4b8e-9d5a-7e1f2c3b4a59/tracks";
+        let _mock_page_1 = mock_request_success(
+            "GET",
+            url,
+            vec![
+                Matcher::UrlEncoded("limit".into(), "2".into()),
+                Matcher::UrlEncoded("offset".into(), "0".into()),
+            ],
+            r#"{"items": [{"id": 1}, {"id": 2}], "totalNumberOfItems": 3}"#,
+        );
+        let _mock_page_2 = mock_request_success(
+            "GET",
+            url,
+            vec![
+                Matcher::UrlEncoded("limit".into(), "2".into()),
+                Matcher::UrlEncoded("offset".into(), "2".into()),
+            ],
+            r#"{"items": [{"id": 3}], "totalNumberOfItems": 3}"#,
+        );
+
+        let client = TidalBuilder::new().max_limit(2).build(credential()).unwrap();
+        let result: Vec<Track> = client.get_all(url, 50).await.unwrap();
+        assert_eq!(result.len(), 3);
+
+        let tracks: Vec<ClientResult<Track>> = client.paginate(url, 50).collect().await;
+        assert_eq!(tracks.len(), 3);
+    }
+
+    #[test]
+    fn pagination_params_zero_limit() {
+        let result = pagination_params(Some(0), None, 10, 50);
+        assert!(matches!(result, Err(ClientError::InvalidLimit)));
+    }
+
+    #[test]
+    fn pagination_params_offset_only() {
+        let params = pagination_params(None, Some(20), 10, 50).unwrap();
+        assert_eq!(params.get("limit"), Some(&"10".to_owned()));
+        assert_eq!(params.get("offset"), Some(&"20".to_owned()));
+    }
+
+    #[test]
+    fn pagination_params_max_limit() {
+        let params = pagination_params(Some(5000), None, 10, 50).unwrap();
+        assert_eq!(params.get("limit"), Some(&"50".to_owned()));
+    }
+
     fn mock_request_success(
         method: &str,
         path: &str,
@@ -611,15 +2425,14 @@ pub mod tests {
         Tidal::new(credential())
     }
 
-    fn credential() -> TidalCredentials {
+    pub fn credential() -> TidalCredentials {
         let session: Session = Session {
             user_id: 1234,
             session_id: "session-id-1".to_owned(),
             country_code: "US".to_owned(),
+            access_token: None,
+            refresh_token: None,
         };
-        TidalCredentials {
-            token: "some_token".to_owned(),
-            session: Some(session),
-        }
+        TidalCredentials::new("some_token").session(Some(session))
     }
 }
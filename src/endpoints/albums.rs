@@ -1,38 +1,140 @@
 //! Endpoint functions relating to albums
 
-use std::collections::HashMap;
+use futures_util::future::try_join;
 
-use crate::client::{ClientResult, Tidal, TidalItems};
-use crate::model::album::Album;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::client::{numeric_id, ClientError, ClientResult, Tidal, TidalItems};
+use crate::model::album::{Album, AlbumReview};
+use crate::model::artist::Artist;
+use crate::model::credit::Credit;
+use crate::model::ModelType;
 use crate::model::track::Track;
 
 pub struct Albums<'a>(pub &'a Tidal);
 
 impl Albums<'_> {
-    pub async fn get(self, id: &str) -> ClientResult<Album> {
-        let url = format!("/albums/{}", id);
-        let result = self.0.get(&url, &mut HashMap::new()).await?;
+    pub async fn get(&self, id: &str) -> ClientResult<Album> {
+        self.get_with(id, HashMap::new()).await
+    }
+
+    /// Same as `get`, sending `extra` as additional query parameters, e.g. for flags this crate
+    /// doesn't know about yet.
+    pub async fn get_with(
+        &self,
+        id: &str,
+        mut extra: HashMap<String, String>,
+    ) -> ClientResult<Album> {
+        let url = format!("/albums/{}", numeric_id(id)?);
+        let result = self.0.get(&url, &mut extra).await?;
         Tidal::convert_result::<Album>(&result)
     }
 
-    pub async fn search(&self, term: &str, limit: Option<u16>) -> ClientResult<Vec<Album>> {
-        let albums = self.0.search(term, limit).await?.albums.items;
+    /// Same as `get`, also returning the response as JSON to inspect fields `Album` doesn't
+    /// model.
+    pub async fn get_raw(&self, id: &str) -> ClientResult<(Album, serde_json::Value)> {
+        let url = format!("/albums/{}", numeric_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        Tidal::convert_result_raw::<Album>(&result)
+    }
+
+    /// Fetches the album unless it didn't change since `etag` was returned. Returns `None` when
+    /// the album is unchanged, otherwise the album together with its current etag.
+    pub async fn get_if_modified(
+        &self,
+        id: &str,
+        etag: Option<String>,
+    ) -> ClientResult<Option<(Album, Option<String>)>> {
+        let url = format!("/albums/{}", numeric_id(id)?);
+        match self.0.get_if_modified(&url, &mut HashMap::new(), etag).await? {
+            Some((result, etag)) => Ok(Some((Tidal::convert_result::<Album>(&result)?, etag))),
+            None => Ok(None),
+        }
+    }
+
+    /// The album together with its tracks, both are fetched concurrently.
+    pub async fn full(&self, id: &str) -> ClientResult<(Album, Vec<Track>)> {
+        try_join(self.get(id), self.tracks(id)).await
+    }
+
+    /// Albums matching a UPC/EAN barcode, e.g. `016861775698`.
+    pub async fn by_upc(&self, upc: &str) -> ClientResult<Vec<Album>> {
+        if upc.is_empty() || !upc.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ClientError::InvalidBarcode(upc.to_owned()));
+        }
+
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert("barcodeId".to_owned(), upc.to_owned());
+        let result = self.0.get("/albums/byBarcodeId", &mut params).await?;
+        let albums = Tidal::convert_result::<TidalItems<Album>>(&result)?.items;
         Ok(albums)
     }
 
+    pub async fn credits(&self, id: &str) -> ClientResult<Vec<Credit>> {
+        let url = format!("/albums/{}/credits", numeric_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        Tidal::convert_result::<Vec<Credit>>(&result)
+    }
+
+    /// The primary artist of an album. When the album only embeds the artist's id and name the
+    /// full artist is fetched.
+    pub async fn primary_artist(&self, album_id: &str) -> ClientResult<Artist> {
+        let artists = self.0.albums().get(album_id).await?.artists.unwrap_or_default();
+        let main_index = artists
+            .iter()
+            .position(|artist| matches!(artist._type, Some(ModelType::Main)))
+            .unwrap_or(0);
+        let artist = artists
+            .into_iter()
+            .nth(main_index)
+            .ok_or(ClientError::MissingField("artists"))?;
+
+        let is_sparse =
+            artist.url.is_none() && artist.picture.is_none() && artist.popularity.is_none();
+        match artist.id {
+            Some(id) if is_sparse => self.0.artists().get(&id.to_string()).await,
+            _ => Ok(artist),
+        }
+    }
+
+    /// Editorial review of the album. Albums without a review fail with a not found error, see
+    /// `ClientError::is_not_found`.
+    pub async fn review(&self, id: &str) -> ClientResult<AlbumReview> {
+        let url = format!("/albums/{}/review", numeric_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        Tidal::convert_result::<AlbumReview>(&result)
+    }
+
+    pub async fn search(&self, term: &str, limit: Option<u16>) -> ClientResult<Vec<Album>> {
+        self.0.searches().albums(term, limit, None).await
+    }
+
     pub async fn tracks(&self, id: &str) -> ClientResult<Vec<Track>> {
-        let url = format!("/albums/{}/tracks", id);
+        let url = format!("/albums/{}/tracks", numeric_id(id)?);
         let result = self.0.get(&url, &mut HashMap::new()).await?;
         let tracks = Tidal::convert_result::<TidalItems<Track>>(&result)?.items;
         Ok(tracks)
     }
+
+    /// Tracks of the album grouped by disc (`volume_number`, tracks without one are on the first
+    /// disc), each disc ordered by `track_number`.
+    pub async fn tracks_by_volume(&self, id: &str) -> ClientResult<BTreeMap<u16, Vec<Track>>> {
+        let mut volumes: BTreeMap<u16, Vec<Track>> = BTreeMap::new();
+        for track in self.tracks(id).await? {
+            volumes.entry(track.volume_number.unwrap_or(1)).or_default().push(track);
+        }
+        for tracks in volumes.values_mut() {
+            tracks.sort_by_key(|track| track.track_number);
+        }
+        Ok(volumes)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::client::tests::{client, mock_request_success_from_file};
-    use mockito::Matcher;
+    use mockito::{mock, Matcher};
 
     #[tokio::test]
     async fn get() {
@@ -53,16 +155,140 @@ mod tests {
         assert_eq!(result.title, expected_result.title);
     }
 
+    #[tokio::test]
+    async fn get_artist_and_artists() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album.json",
+        );
+
+        let result: Album = client().albums().get("79914998").await.unwrap();
+        let artist = result.artist.unwrap();
+        assert_eq!(artist.id, Some(37312));
+        assert_eq!(artist.name, Some("Trivium".to_owned()));
+        let artists = result.artists.unwrap();
+        assert_eq!(artists.len(), 1);
+        assert_eq!(artists[0].id, Some(37312));
+    }
+
+    #[tokio::test]
+    async fn get_if_modified() {
+        let mock_modified = mock("GET", "/albums/79914998")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"1600534099032\"")
+            .with_body_from_file("tests/files/album.json")
+            .expect(1)
+            .create();
+        let mock_not_modified = mock("GET", "/albums/79914998")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", "\"1600534099032\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+
+        let client = client();
+        let (album, etag) = client
+            .albums()
+            .get_if_modified("79914998", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(album.id, Some(79914998));
+        assert_eq!(etag, Some("\"1600534099032\"".to_owned()));
+
+        let result = client.albums().get_if_modified("79914998", etag).await.unwrap();
+        assert!(result.is_none());
+        mock_modified.assert();
+        mock_not_modified.assert();
+    }
+
+    #[tokio::test]
+    async fn review() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998/review",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album_review.json",
+        );
+
+        let result: AlbumReview = client().albums().review("79914998").await.unwrap();
+        assert_eq!(result.source, Some("TiVo".to_owned()));
+        assert_eq!(
+            result.summary,
+            Some("Trivium return to their thrash roots with a fresh drummer.".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn review_missing() {
+        let _mock = mock("GET", "/albums/7460040/review")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(404)
+            .with_body(r#"{"status": 404, "subStatus": 2001, "userMessage": "Review not found"}"#)
+            .create();
+
+        let result = client().albums().review("7460040").await;
+        assert!(result.unwrap_err().is_not_found());
+    }
+
+    #[tokio::test]
+    async fn get_does_not_consume_accessor() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album.json",
+        );
+
+        let client = client();
+        let first: Album = client.albums().get("79914998").await.unwrap();
+        let second: Album = client.albums().get("79914998").await.unwrap();
+        assert_eq!(first.id, second.id);
+
+        let albums = client.albums();
+        albums.get("79914998").await.unwrap();
+        let result: Album = albums.get("79914998").await.unwrap();
+        assert_eq!(result.title, Some("My Album".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn by_upc() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/albums/byBarcodeId",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("barcodeId".into(), "016861744601".into()),
+            ],
+            "tests/files/album_by_upc.json",
+        );
+
+        let result: Vec<Album> = client().albums().by_upc("016861744601").await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, Some(79914998));
+        assert_eq!(result[0].upc, Some("016861744601".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn by_upc_invalid_barcode() {
+        let result = client().albums().by_upc("01686-174505").await;
+        assert!(matches!(result, Err(ClientError::InvalidBarcode(_))));
+    }
+
     #[tokio::test]
     async fn search() {
         let _mock = mock_request_success_from_file(
             "GET",
-            "/search",
+            "/search/albums",
             vec![
                 Matcher::UrlEncoded("countryCode".into(), "US".into()),
                 Matcher::UrlEncoded("query".into(), "trivium".into()),
             ],
-            "tests/files/search.json",
+            "tests/files/search_albums.json",
         )
         .create();
 
@@ -87,4 +313,91 @@ mod tests {
         };
         assert_eq!(result[0].title, expected_first_result.title);
     }
+
+    #[tokio::test]
+    async fn tracks_by_volume() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/albums/79914984/tracks",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album_tracks_volumes.json",
+        );
+
+        let result = client().albums().tracks_by_volume("79914984").await.unwrap();
+        let titles: Vec<(u16, Vec<&str>)> = result
+            .iter()
+            .map(|(volume, tracks)| {
+                (*volume, tracks.iter().filter_map(|track| track.title.as_deref()).collect())
+            })
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                (1, vec!["Intro: Churchill's Speech", "Aces High"]),
+                (2, vec!["Fear of the Dark", "Iron Maiden", "The Trooper"]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn primary_artist() {
+        let _mock_album = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album.json",
+        );
+        let _mock_artist = mock_request_success_from_file(
+            "GET",
+            "/artists/37312",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/artist.json",
+        );
+
+        let result: Artist = client().albums().primary_artist("79914998").await.unwrap();
+        assert_eq!(result.id, Some(37312));
+        assert_eq!(result.popularity, Some(43));
+        assert_eq!(
+            result.picture,
+            Some("8cd9716d-0206-46a6-a70a-7dc2e427d11b".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn credits() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998/credits",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album_credits.json",
+        );
+
+        let result: Vec<Credit> = client().albums().credits("79914998").await.unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0]._type, "Producer");
+        assert_eq!(result[1].contributors.len(), 2);
+        assert_eq!(result[1].contributors[0].name, Some("Matthew Heafy".to_owned()));
+        assert_eq!(result[2].contributors[0].id, None);
+    }
+
+    #[tokio::test]
+    async fn full() {
+        let _mock_album = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album.json",
+        );
+        let _mock_tracks = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998/tracks",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album_tracks.json",
+        );
+
+        let (album, tracks) = client().albums().full("79914998").await.unwrap();
+        assert_eq!(album.title, Some("My Album".to_owned()));
+        assert_eq!(tracks.len(), 11);
+        assert_eq!(tracks[0].title, Some("The Sin and the Sentence".to_owned()));
+    }
 }
@@ -1,38 +1,144 @@
 //! Endpoint functions relateed to artists
 
+use serde::Deserialize;
+
 use std::collections::HashMap;
 
-use crate::client::{ClientResult, Tidal, TidalItems};
+use crate::client::{
+    numeric_id, pagination_params, ClientError, ClientResult, Tidal, TidalItems,
+};
 use crate::model::album::Album;
-use crate::model::artist::Artist;
+use crate::model::artist::{Artist, ArtistBio, ExternalLink};
+use crate::model::track::Track;
 
 pub struct Artists<'a>(pub &'a Tidal);
 
+// The source of artist links is sent next to the items instead of with every link
+#[derive(Debug, Deserialize)]
+struct ArtistLinks {
+    items: Vec<ExternalLink>,
+    source: Option<String>,
+}
+
 impl Artists<'_> {
     pub async fn get(&self, id: &str) -> ClientResult<Artist> {
-        let url = format!("/artists/{}", id);
-        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        self.get_with(id, HashMap::new()).await
+    }
+
+    /// Same as `get`, sending `extra` as additional query parameters, e.g. for flags this crate
+    /// doesn't know about yet.
+    pub async fn get_with(
+        &self,
+        id: &str,
+        mut extra: HashMap<String, String>,
+    ) -> ClientResult<Artist> {
+        let url = format!("/artists/{}", numeric_id(id)?);
+        let result = self.0.get(&url, &mut extra).await?;
         Tidal::convert_result::<Artist>(&result)
     }
 
+    /// Same as `get`, also returning the response as JSON to inspect fields `Artist` doesn't
+    /// model.
+    pub async fn get_raw(&self, id: &str) -> ClientResult<(Artist, serde_json::Value)> {
+        let url = format!("/artists/{}", numeric_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        Tidal::convert_result_raw::<Artist>(&result)
+    }
+
+    /// Same as `get` but asks Tidal to include the contributor roles of the artist, useful for
+    /// session musicians and producers.
+    pub async fn get_full(&self, id: &str) -> ClientResult<Artist> {
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert("includeContributors".to_owned(), "true".to_owned());
+        self.get_with(id, params).await
+    }
+
+    /// Tracks of the artist's radio, i.e. the items of their `ARTIST_MIX`. Fails with
+    /// `ClientError::MissingField` when Tidal has no radio for the artist.
+    pub async fn radio(&self, id: &str) -> ClientResult<Vec<Track>> {
+        let artist = self.get(id).await?;
+        let mix_id = artist
+            .mixes
+            .as_ref()
+            .and_then(|mixes| mixes.get("ARTIST_MIX"))
+            .ok_or(ClientError::MissingField("ARTIST_MIX"))?;
+        self.0.mixes().get_items(mix_id).await
+    }
+
     pub async fn search(&self, term: &str, limit: Option<u16>) -> ClientResult<Vec<Artist>> {
-        let artists = self.0.search(term, limit).await?.artists.items;
-        Ok(artists)
+        self.0.searches().artists(term, limit, None).await
     }
 
     pub async fn albums(&self, id: &str) -> ClientResult<Vec<Album>> {
-        let url = format!("/artists/{}/albums", id);
+        let url = format!("/artists/{}/albums", numeric_id(id)?);
         let result = self.0.get(&url, &mut HashMap::new()).await?;
         let albums = Tidal::convert_result::<TidalItems<Album>>(&result)?.items;
         Ok(albums)
     }
+
+    /// Every album of the artist, `albums` only returns the first page.
+    pub async fn all_albums(&self, id: &str) -> ClientResult<Vec<Album>> {
+        let url = format!("/artists/{}/albums", numeric_id(id)?);
+        self.0.get_all(&url, 50).await
+    }
+
+    /// Every album of the artist paired with the URL of its cover, see `Album::cover_url`.
+    pub async fn album_covers(
+        &self,
+        id: &str,
+        size: u16,
+    ) -> ClientResult<Vec<(u32, Option<String>)>> {
+        let covers = self
+            .albums(id)
+            .await?
+            .iter()
+            .filter_map(|album| Some((album.id?, album.cover_url(size))))
+            .collect();
+        Ok(covers)
+    }
+
+    pub async fn similar(&self, id: &str, limit: Option<u16>) -> ClientResult<Vec<Artist>> {
+        let url = format!("/artists/{}/similar", numeric_id(id)?);
+        let mut params = pagination_params(limit, None, 20, self.0.max_limit())?;
+        let result = self.0.get(&url, &mut params).await?;
+        let artists = Tidal::convert_result::<TidalItems<Artist>>(&result)?.items;
+        Ok(artists)
+    }
+
+    /// Websites of the artist, e.g. the official homepage and social networks.
+    pub async fn links(&self, id: &str) -> ClientResult<Vec<ExternalLink>> {
+        let url = format!("/artists/{}/links", numeric_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        let ArtistLinks { items, source } = Tidal::convert_result::<ArtistLinks>(&result)?;
+        let links = items
+            .into_iter()
+            .map(|link| ExternalLink { source: link.source.or_else(|| source.clone()), ..link })
+            .collect();
+        Ok(links)
+    }
+
+    pub async fn bio(&self, id: &str) -> ClientResult<ArtistBio> {
+        let url = format!("/artists/{}/bio", numeric_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        Tidal::convert_result::<ArtistBio>(&result)
+    }
+
+    pub async fn top_tracks(&self, id: &str, limit: Option<u16>) -> ClientResult<Vec<Track>> {
+        let url = format!("/artists/{}/toptracks", numeric_id(id)?);
+        let mut params =
+            pagination_params(limit, None, self.0.default_limit(), self.0.max_limit())?;
+        let result = self.0.get(&url, &mut params).await?;
+        let tracks = Tidal::convert_result::<TidalItems<Track>>(&result)?.items;
+        Ok(tracks)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::client::tests::{client, mock_request_success_from_file};
-    use mockito::Matcher;
+    use crate::model::artist::ArtistType;
+    use mockito::{mock, Matcher};
 
     #[tokio::test]
     async fn get() {
@@ -53,22 +159,137 @@ mod tests {
         assert_eq!(result.name, expected_result.name);
     }
 
+    #[tokio::test]
+    async fn get_invalid_id() {
+        let result = client().artists().get("trivium").await;
+        assert!(matches!(result, Err(ClientError::InvalidId(_))));
+    }
+
+    #[tokio::test]
+    async fn related_invalid_id() {
+        let client = client();
+        assert!(matches!(client.artists().albums("trivium").await, Err(ClientError::InvalidId(_))));
+        assert!(matches!(client.artists().bio("37312/..").await, Err(ClientError::InvalidId(_))));
+        assert!(matches!(
+            client.artists().top_tracks("", None).await,
+            Err(ClientError::InvalidId(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_with() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("someNewFlag".into(), "true".into()),
+            ],
+            "tests/files/artist.json",
+        );
+
+        let mut extra: HashMap<String, String> = HashMap::new();
+        extra.insert("someNewFlag".to_owned(), "true".to_owned());
+        let result: Artist = client().artists().get_with("37312", extra).await.unwrap();
+        assert_eq!(result.id, Some(37312));
+    }
+
+    #[tokio::test]
+    async fn get_raw() {
+        let _mock = mock("GET", "/artists/37311")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(200)
+            .with_body(r#"{"id": 37311, "name": "Killswitch Engage", "spotlighted": true}"#)
+            .create();
+
+        let (artist, raw) = client().artists().get_raw("37311").await.unwrap();
+        assert_eq!(artist.name, Some("Killswitch Engage".to_owned()));
+        assert_eq!(raw["spotlighted"], true);
+    }
+
+    #[tokio::test]
+    async fn get_mixes() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/artist.json",
+        );
+
+        let result: Artist = client().artists().get("37312").await.unwrap();
+        let mixes = result.mixes.unwrap();
+        assert_eq!(mixes["ARTIST_MIX"], "000ec0b01da1ddd752ec5dee553d48");
+    }
+
+    #[tokio::test]
+    async fn radio() {
+        let _mock_artist = mock_request_success_from_file(
+            "GET",
+            "/artists/37312",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/artist.json",
+        );
+        let _mock_mix = mock_request_success_from_file(
+            "GET",
+            "/mixes/000ec0b01da1ddd752ec5dee553d48/items",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/mix_items.json",
+        );
+
+        let result: Vec<Track> = client().artists().radio("37312").await.unwrap();
+        assert!(!result.is_empty());
+        assert_eq!(result[0].title, Some("Beyond Oblivion".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn radio_without_mix() {
+        let _mock = mock("GET", "/artists/3635103")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(200)
+            .with_body(r#"{"id": 3635103, "name": "Asking Alexandria"}"#)
+            .create();
+
+        let result = client().artists().radio("3635103").await;
+        assert!(matches!(result, Err(ClientError::MissingField("ARTIST_MIX"))));
+    }
+
+    #[tokio::test]
+    async fn get_full() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("includeContributors".into(), "true".into()),
+            ],
+            "tests/files/artist.json",
+        );
+
+        let result: Artist = client().artists().get_full("37312").await.unwrap();
+        let artist_types = result.artist_types.unwrap();
+        assert_eq!(artist_types.len(), 2);
+        assert!(matches!(artist_types[1], ArtistType::Contributor));
+        assert_eq!(result.artist_roles.unwrap()[1].category, Some("Songwriter".to_owned()));
+    }
+
     #[tokio::test]
     async fn search() {
         let _mock = mock_request_success_from_file(
             "GET",
-            "/search",
+            "/search/artists",
             vec![
                 Matcher::UrlEncoded("countryCode".into(), "US".into()),
                 Matcher::UrlEncoded("query".into(), "trivium".into()),
             ],
-            "tests/files/search.json",
+            "tests/files/search_artists.json",
         )
         .create();
 
         let result: Vec<Artist> = client().artists().search("trivium", None).await.unwrap();
 
         assert_eq!(result.len(), 10);
+        // Search results don't come with mixes
+        assert!(result[0].mixes.is_none());
     }
 
     #[tokio::test]
@@ -89,4 +310,93 @@ mod tests {
         assert_eq!(result[0].id, expected_first_result.id);
         assert_eq!(result[0].title, expected_first_result.title);
     }
+
+    #[tokio::test]
+    async fn top_tracks() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312/toptracks",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/artist_toptracks.json",
+        );
+
+        let result: Vec<Track> = client().artists().top_tracks("37312", None).await.unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].title, Some("The Sin and the Sentence".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn bio() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312/bio",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/artist_bio.json",
+        );
+
+        let result: ArtistBio = client().artists().bio("37312").await.unwrap();
+        assert_eq!(
+            result.summary,
+            Some("Florida metal band blending thrash, metalcore and melodic death metal.".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn links() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312/links",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/artist_links.json",
+        );
+
+        let result: Vec<ExternalLink> = client().artists().links("37312").await.unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].url, Some("http://www.trivium.org".to_owned()));
+        assert_eq!(result[0].site_name, Some("OFFICIAL_HOMEPAGE".to_owned()));
+        assert_eq!(result[2].source, Some("TiVo".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn similar() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312/similar",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "20".into()),
+            ],
+            "tests/files/artist_similar.json",
+        );
+
+        let result: Vec<Artist> = client().artists().similar("37312", None).await.unwrap();
+        assert_eq!(result.len(), 5);
+        assert_eq!(result[0].name, Some("Killswitch Engage".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn album_covers() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/artists/37312/albums",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/artist_albums.json",
+        );
+
+        let result = client().artists().album_covers("37312", 640).await.unwrap();
+        assert_eq!(result.len(), 10);
+        assert_eq!(
+            result[0],
+            (
+                138458220,
+                Some(
+                    "https://resources.tidal.com/images/404d8a74/e8d5/4586/914f/d1ffddc2377e/640x640.jpg"
+                        .to_owned()
+                )
+            )
+        );
+    }
 }
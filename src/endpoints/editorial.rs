@@ -0,0 +1,81 @@
+//! Endpoint functions related to Tidal's editorial lists
+
+use serde::de::DeserializeOwned;
+
+use crate::client::{pagination_params, ClientResult, Tidal, TidalItems};
+use crate::model::album::Album;
+use crate::model::track::Track;
+
+pub struct Editorial<'a>(pub &'a Tidal);
+
+impl Editorial<'_> {
+    /// Newly released albums picked by Tidal's editors.
+    pub async fn new_albums(&self, limit: Option<u16>) -> ClientResult<Vec<Album>> {
+        self.featured("/featured/new/albums", limit).await
+    }
+
+    /// Tracks recommended by Tidal's editors.
+    pub async fn recommended_tracks(&self, limit: Option<u16>) -> ClientResult<Vec<Track>> {
+        self.featured("/featured/recommended/tracks", limit).await
+    }
+
+    /// Currently most popular albums.
+    pub async fn top_albums(&self, limit: Option<u16>) -> ClientResult<Vec<Album>> {
+        self.featured("/featured/top/albums", limit).await
+    }
+
+    async fn featured<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        limit: Option<u16>,
+    ) -> ClientResult<Vec<T>> {
+        let mut params =
+            pagination_params(limit, None, self.0.default_limit(), self.0.max_limit())?;
+        let result = self.0.get(url, &mut params).await?;
+        let items = Tidal::convert_result::<TidalItems<T>>(&result)?.items;
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::{client, mock_request_success_from_file};
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn new_albums() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/featured/new/albums",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "3".into()),
+            ],
+            "tests/files/featured_new_albums.json",
+        );
+
+        let result: Vec<Album> = client().editorial().new_albums(Some(3)).await.unwrap();
+        let ids: Vec<Option<u32>> = result.iter().map(|album| album.id).collect();
+        assert_eq!(ids, vec![Some(160000003), Some(160000001), Some(160000002)]);
+        assert_eq!(result[0].title, Some("What the Dead Men Say".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn recommended_tracks() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/featured/recommended/tracks",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/featured_recommended_tracks.json",
+        );
+
+        let result: Vec<Track> = client().editorial().recommended_tracks(None).await.unwrap();
+        let ids: Vec<Option<u32>> = result.iter().map(|track| track.id).collect();
+        assert_eq!(ids, vec![Some(79915002), Some(79915000), Some(79915001)]);
+        assert_eq!(result[1].title, Some("Beyond Oblivion".to_owned()));
+    }
+}
@@ -0,0 +1,85 @@
+//! Endpoint functions related to the user's favorites
+
+use std::collections::HashMap;
+
+use crate::client::{ClientResult, Tidal};
+use crate::model::favorite::FavoriteIds;
+
+pub struct Favorites<'a>(pub &'a Tidal);
+
+impl Favorites<'_> {
+    /// Ids of all favorited tracks, albums, artists and playlists in a single request.
+    pub async fn all_ids(&self) -> ClientResult<FavoriteIds> {
+        let url = format!("/users/{}/favorites/ids", self.0.user_id());
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert("filter".to_owned(), "ALL".to_owned());
+        let result = self.0.get(&url, &mut params).await?;
+        Tidal::convert_result::<FavoriteIds>(&result)
+    }
+
+    /// Checks whether a track is a favorite. Pass the ids from a previous `all_ids` call to check
+    /// many tracks without a request per track.
+    pub async fn is_track_favorite(
+        &self,
+        track_id: u32,
+        favorite_ids: Option<&FavoriteIds>,
+    ) -> ClientResult<bool> {
+        match favorite_ids {
+            Some(favorite_ids) => Ok(favorite_ids.tracks.contains(&track_id)),
+            None => Ok(self.all_ids().await?.tracks.contains(&track_id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::{client, mock_request_success_from_file};
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn all_ids() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/users/1234/favorites/ids",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("filter".into(), "ALL".into()),
+            ],
+            "tests/files/favorite_ids.json",
+        );
+
+        let result: FavoriteIds = client().favorites().all_ids().await.unwrap();
+        assert_eq!(result.tracks.len(), 2);
+        assert!(result.tracks.contains(&79914999));
+        assert!(result.albums.contains(&79914998));
+        assert_eq!(result.artists.len(), 3);
+        assert!(result
+            .playlists
+            .contains("7ce7df87-6d37-4465-80db-84535a4e44a4"));
+    }
+
+    #[tokio::test]
+    async fn is_track_favorite() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/users/1234/favorites/ids",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("filter".into(), "ALL".into()),
+            ],
+            "tests/files/favorite_ids.json",
+        );
+
+        let client = client();
+        assert!(client.favorites().is_track_favorite(79914999, None).await.unwrap());
+
+        let favorite_ids = client.favorites().all_ids().await.unwrap();
+        let is_favorite = client
+            .favorites()
+            .is_track_favorite(1, Some(&favorite_ids))
+            .await
+            .unwrap();
+        assert!(!is_favorite);
+    }
+}
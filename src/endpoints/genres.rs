@@ -0,0 +1,105 @@
+//! Endpoint functions related to genres
+
+use std::collections::HashMap;
+
+use crate::client::{genre_path, pagination_params, ClientResult, Tidal, TidalItems};
+use crate::model::album::Album;
+use crate::model::genre::Genre;
+use crate::model::track::Track;
+
+pub struct Genres<'a>(pub &'a Tidal);
+
+impl Genres<'_> {
+    pub async fn list(&self) -> ClientResult<Vec<Genre>> {
+        let result = self.0.get("/genres", &mut HashMap::new()).await?;
+        Tidal::convert_result::<Vec<Genre>>(&result)
+    }
+
+    /// Albums of a genre, `path` is the `Genre::path` of a genre from `list`.
+    pub async fn albums(&self, path: &str, limit: Option<u16>) -> ClientResult<Vec<Album>> {
+        let url = format!("/genres/{}/albums", genre_path(path)?);
+        let mut params =
+            pagination_params(limit, None, self.0.default_limit(), self.0.max_limit())?;
+        let result = self.0.get(&url, &mut params).await?;
+        let albums = Tidal::convert_result::<TidalItems<Album>>(&result)?.items;
+        Ok(albums)
+    }
+
+    /// Tracks of a genre, `path` is the `Genre::path` of a genre from `list`.
+    pub async fn tracks(&self, path: &str, limit: Option<u16>) -> ClientResult<Vec<Track>> {
+        let url = format!("/genres/{}/tracks", genre_path(path)?);
+        let mut params =
+            pagination_params(limit, None, self.0.default_limit(), self.0.max_limit())?;
+        let result = self.0.get(&url, &mut params).await?;
+        let tracks = Tidal::convert_result::<TidalItems<Track>>(&result)?.items;
+        Ok(tracks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::{client, mock_request_success_from_file};
+    use crate::client::ClientError;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn list() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/genres",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/genres.json",
+        );
+
+        let result: Vec<Genre> = client().genres().list().await.unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].name, Some("Metal".to_owned()));
+        assert_eq!(result[0].path, Some("Metal".to_owned()));
+        assert_eq!(result[0].has_albums, Some(true));
+        assert_eq!(result[2].path, Some("Hip-Hop".to_owned()));
+        assert_eq!(result[2].has_artists, Some(false));
+    }
+
+    #[tokio::test]
+    async fn albums() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/genres/Metal/albums",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "3".into()),
+            ],
+            "tests/files/featured_new_albums.json",
+        );
+
+        let result: Vec<Album> = client().genres().albums("Metal", Some(3)).await.unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].id, Some(160000003));
+    }
+
+    #[tokio::test]
+    async fn tracks() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/genres/Hip-Hop/tracks",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/album_tracks.json",
+        );
+
+        let result: Vec<Track> = client().genres().tracks("Hip-Hop", None).await.unwrap();
+        assert_eq!(result.len(), 11);
+        assert_eq!(result[0].title, Some("The Sin and the Sentence".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn invalid_path() {
+        let result = client().genres().albums("../users/1234/favorites", None).await;
+        assert!(matches!(result, Err(ClientError::InvalidId(_))));
+        let result = client().genres().tracks("Metal?limit=100", None).await;
+        assert!(matches!(result, Err(ClientError::InvalidId(_))));
+    }
+}
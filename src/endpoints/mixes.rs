@@ -0,0 +1,106 @@
+//! Endpoint functions related to mixes
+
+use serde::Deserialize;
+
+use std::collections::HashMap;
+
+use crate::client::{mix_id, pagination_params, ClientResult, Tidal, TidalItems};
+use crate::model::mix::Mix;
+use crate::model::track::Track;
+
+pub struct Mixes<'a>(pub &'a Tidal);
+
+// Items of a mix are wrapped together with their type
+#[derive(Debug, Deserialize)]
+struct MixItem {
+    item: Option<Track>,
+    #[serde(rename = "type")]
+    _type: String,
+}
+
+impl Mixes<'_> {
+    /// Tracks of a mix, other items like videos are skipped.
+    pub async fn get_items(&self, mix_id: &str) -> ClientResult<Vec<Track>> {
+        self.items(mix_id, None).await
+    }
+
+    pub(crate) async fn items(&self, id: &str, limit: Option<u16>) -> ClientResult<Vec<Track>> {
+        let url = format!("/mixes/{}/items", mix_id(id)?);
+        let mut params =
+            pagination_params(limit, None, self.0.default_limit(), self.0.max_limit())?;
+        let result = self.0.get(&url, &mut params).await?;
+        let tracks = Tidal::convert_result::<TidalItems<MixItem>>(&result)?
+            .items
+            .into_iter()
+            .filter(|mix_item| mix_item._type == "track")
+            .filter_map(|mix_item| mix_item.item)
+            .collect();
+        Ok(tracks)
+    }
+
+    /// Personalized mixes of the authenticated user, e.g. their daily discovery mix.
+    pub async fn user_mixes(&self) -> ClientResult<Vec<Mix>> {
+        let url = format!("/users/{}/mixes", self.0.user_id());
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        let mixes = Tidal::convert_result::<TidalItems<Mix>>(&result)?.items;
+        Ok(mixes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::{client, mock_request_success_from_file};
+    use crate::client::ClientError;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn get_items() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/mixes/0017159e6a1f34ae3d981792d72ecf/items",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/mix_items.json",
+        );
+
+        let result: Vec<Track> = client()
+            .mixes()
+            .get_items("0017159e6a1f34ae3d981792d72ecf")
+            .await
+            .unwrap();
+        assert!(!result.is_empty());
+        assert_eq!(result[0].id, Some(79915000));
+        assert_eq!(result[0].title, Some("Beyond Oblivion".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn get_items_invalid_id() {
+        for id in &["", "../users/1234/favorites/ids", "0017159e6a1f34ae3d981792d72ecf?limit=1"] {
+            let result = client().mixes().get_items(id).await;
+            assert!(matches!(result, Err(ClientError::InvalidId(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn user_mixes() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/users/1234/mixes",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/user_mixes.json",
+        );
+
+        let result: Vec<Mix> = client().mixes().user_mixes().await.unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].id, Some("0017159e6a1f34ae3d981792d72ecf".to_owned()));
+        assert_eq!(result[0].title, Some("My Daily Discovery".to_owned()));
+        assert_eq!(result[0].sub_title, Some("Trivium, Gojira, Mastodon".to_owned()));
+        assert_eq!(result[0].mix_type, Some("DISCOVERY_MIX".to_owned()));
+        let images = result[0].images.as_ref().unwrap();
+        assert_eq!(images["SMALL"].width, Some(320));
+        assert_eq!(result[1].mix_type, Some("TRACK_MIX".to_owned()));
+    }
+}
@@ -1,5 +1,9 @@
 pub mod albums;
 pub mod artists;
+pub mod editorial;
+pub mod favorites;
+pub mod genres;
+pub mod mixes;
 pub mod playlists;
 pub mod search;
 pub mod tracks;
@@ -7,6 +11,10 @@ pub mod tracks;
 use crate::client::Tidal;
 use crate::endpoints::albums::*;
 use crate::endpoints::artists::*;
+use crate::endpoints::editorial::*;
+use crate::endpoints::favorites::*;
+use crate::endpoints::genres::*;
+use crate::endpoints::mixes::*;
 use crate::endpoints::playlists::*;
 use crate::endpoints::search::*;
 use crate::endpoints::tracks::*;
@@ -22,6 +30,22 @@ impl Tidal {
         Artists(&self)
     }
 
+    pub const fn editorial(&self) -> Editorial {
+        Editorial(&self)
+    }
+
+    pub const fn favorites(&self) -> Favorites {
+        Favorites(&self)
+    }
+
+    pub const fn genres(&self) -> Genres {
+        Genres(&self)
+    }
+
+    pub const fn mixes(&self) -> Mixes {
+        Mixes(&self)
+    }
+
     pub const fn playlists(&self) -> Playlists {
         Playlists(&self)
     }
@@ -1,65 +1,420 @@
 //! Endpoint functions related to playlists
 
-use std::collections::HashMap;
+use futures_util::future::try_join;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 
-use crate::client::{ClientResult, Tidal, TidalItems};
-use crate::model::playlist::Playlist;
-use crate::model::track::Track;
+use std::collections::{HashMap, HashSet};
+
+use crate::client::{playlist_id, ClientError, ClientResult, Tidal, TidalItems};
+use crate::model::playlist::{
+    AddTracksResult, Playlist, PlaylistDiff, PlaylistEntry, PlaylistFolder, PlaylistItem,
+    SyncReport,
+};
+use crate::model::track::{Track, TrackId};
 
 pub struct Playlists<'a>(pub &'a Tidal);
 
+// Most tracks `sync_into` adds to a playlist in one request
+const ADD_CHUNK_SIZE: usize = 50;
+
+// Items of a playlist are wrapped together with their type
+#[derive(Debug, Deserialize)]
+struct RawPlaylistItem {
+    item: RawPlaylistTrack,
+    #[serde(rename = "type")]
+    item_type: String,
+}
+
+// Tidal adds the position and the date the item was added to the item itself
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPlaylistTrack {
+    #[serde(flatten)]
+    track: Track,
+    date_added: Option<String>,
+    index: Option<u32>,
+}
+
+// Items of the collection's folders wrap the folder or playlist in `data`
+#[derive(Debug, Deserialize)]
+struct RawFolderItems<T> {
+    items: Vec<RawFolderItem<T>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFolderItem<T> {
+    data: T,
+}
+
+// Tracks without an id can't be added, e.g. partial tracks of another response
+fn track_ids(tracks: impl IntoIterator<Item = impl Into<TrackId>>) -> ClientResult<Vec<u32>> {
+    tracks
+        .into_iter()
+        .map(|track| track.into().0.ok_or(ClientError::MissingField("id")))
+        .collect()
+}
+
 impl Playlists<'_> {
     pub async fn get(&self, id: &str) -> ClientResult<Playlist> {
-        let url = format!("/playlists/{}", id);
-        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        self.get_with(id, HashMap::new()).await
+    }
+
+    /// Same as `get`, sending `extra` as additional query parameters, e.g. for flags this crate
+    /// doesn't know about yet.
+    pub async fn get_with(
+        &self,
+        id: &str,
+        mut extra: HashMap<String, String>,
+    ) -> ClientResult<Playlist> {
+        let url = format!("/playlists/{}", playlist_id(id)?);
+        let result = self.0.get(&url, &mut extra).await?;
         Tidal::convert_result::<Playlist>(&result)
     }
 
+    /// Same as `get`, also returning the response as JSON to inspect fields `Playlist` doesn't
+    /// model.
+    pub async fn get_raw(&self, id: &str) -> ClientResult<(Playlist, serde_json::Value)> {
+        let url = format!("/playlists/{}", playlist_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        Tidal::convert_result_raw::<Playlist>(&result)
+    }
+
+    /// Fetches several playlists concurrently, see `TidalBuilder::max_concurrency`. Playlists are
+    /// returned in the order of `ids`, the first failed request fails the whole call.
+    pub async fn get_many(&self, ids: &[&str]) -> ClientResult<Vec<Playlist>> {
+        self.0.fan_out(ids, |id| self.get(id)).await.into_iter().collect()
+    }
+
     pub async fn search(&self, term: &str, limit: Option<u16>) -> ClientResult<Vec<Playlist>> {
-        let playlists = self.0.search(term, limit).await?.playlists.items;
-        Ok(playlists)
+        self.0.searches().playlists(term, limit, None).await
+    }
+
+    /// A page of playlists matching `term` starting at `offset`, `total_number_of_items` is the
+    /// number of all matching playlists.
+    pub async fn search_page(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<TidalItems<Playlist>> {
+        self.0.searches().playlists_page(term, limit, offset).await
     }
 
     pub async fn tracks(&self, id: &str) -> ClientResult<Vec<Track>> {
-        let url = format!("/playlists/{}/tracks", id);
+        let url = format!("/playlists/{}/tracks", playlist_id(id)?);
         let result = self.0.get(&url, &mut HashMap::new()).await?;
         let tracks = Tidal::convert_result::<TidalItems<Track>>(&result)?.items;
         Ok(tracks)
     }
 
-    pub async fn create(&self, title: &str, description: &str) -> ClientResult<Playlist> {
-        let user_id = self
+    /// Every track of the playlist, `tracks` only returns the first page.
+    pub async fn all_tracks(&self, id: &str) -> ClientResult<Vec<Track>> {
+        let url = format!("/playlists/{}/tracks", playlist_id(id)?);
+        self.0.get_all(&url, 50).await
+    }
+
+    /// Every track of the playlist together with its position and when it was added. Items that
+    /// aren't tracks, e.g. videos, are skipped.
+    pub async fn items(&self, id: &str) -> ClientResult<Vec<PlaylistItem>> {
+        let url = format!("/playlists/{}/items", playlist_id(id)?);
+        let items = self
             .0
-            .credentials
-            .session
-            .as_ref()
-            .expect("A valid session must be initialized")
-            .user_id;
+            .get_all::<RawPlaylistItem>(&url, 50)
+            .await?
+            .into_iter()
+            .filter(|raw| raw.item_type == "track")
+            .map(|raw| PlaylistItem {
+                item: raw.item.track,
+                date_added: raw.item.date_added,
+                index: raw.item.index,
+            })
+            .collect();
+        Ok(items)
+    }
+
+    /// Every item of the playlist, tracks as well as videos.
+    pub async fn entries(&self, id: &str) -> ClientResult<Vec<PlaylistEntry>> {
+        let url = format!("/playlists/{}/items", playlist_id(id)?);
+        self.0.get_all(&url, 50).await
+    }
+
+    /// Compares the tracks of two playlists by track id. Both playlists are fetched concurrently.
+    pub async fn diff(&self, a_uuid: &str, b_uuid: &str) -> ClientResult<PlaylistDiff> {
+        let (a_tracks, b_tracks) =
+            try_join(self.all_tracks(a_uuid), self.all_tracks(b_uuid)).await?;
+        let a_ids: Vec<u32> = a_tracks.iter().filter_map(|track| track.id).collect();
+        let b_ids: Vec<u32> = b_tracks.iter().filter_map(|track| track.id).collect();
+        let a_set: HashSet<u32> = a_ids.iter().copied().collect();
+        let b_set: HashSet<u32> = b_ids.iter().copied().collect();
+
+        let (common, only_in_a) = a_ids.into_iter().partition(|id| b_set.contains(id));
+        let only_in_b = b_ids.into_iter().filter(|id| !a_set.contains(id)).collect();
+        Ok(PlaylistDiff {
+            only_in_a,
+            only_in_b,
+            common,
+        })
+    }
+
+    /// Adds the tracks of `source_uuid` missing in `target_uuid` to the target playlist. With
+    /// `remove_extras` the tracks of the target that aren't in the source are removed as well,
+    /// videos of the target are kept.
+    pub async fn sync_into(
+        &self,
+        source_uuid: &str,
+        target_uuid: &str,
+        remove_extras: bool,
+    ) -> ClientResult<SyncReport> {
+        let (source, target) =
+            try_join(self.all_tracks(source_uuid), self.entries(target_uuid)).await?;
+        let source_ids: HashSet<u32> = source.iter().filter_map(|track| track.id).collect();
+        let mut target_ids: HashSet<u32> = target
+            .iter()
+            .filter_map(|entry| match entry {
+                PlaylistEntry::Track(track) => track.id,
+                PlaylistEntry::Video(_) => None,
+            })
+            .collect();
+
+        // `insert` returns false for ids already in the target or added twice from the source
+        let to_add: Vec<u32> = source
+            .iter()
+            .filter_map(|track| track.id)
+            .filter(|id| target_ids.insert(*id))
+            .collect();
+        // Positions count every item of the playlist, videos included
+        let to_remove: Vec<u32> = target
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| match entry {
+                PlaylistEntry::Track(track) => {
+                    track.id.map_or(false, |id| !source_ids.contains(&id))
+                }
+                PlaylistEntry::Video(_) => false,
+            })
+            .map(|(index, _)| index as u32)
+            .collect();
+
+        let mut report = SyncReport::default();
+        // Remove first, so the positions still match the fetched items. Every mutation fetches a
+        // new etag since the previous one invalidates it.
+        if remove_extras && !to_remove.is_empty() {
+            self.remove_items(target_uuid, &to_remove).await?;
+            report.removed = to_remove.len();
+        }
+        if !to_add.is_empty() {
+            // Tidal may skip some of the tracks, e.g. ones that aren't available
+            let result = self
+                .add_tracks_chunked(target_uuid, to_add.iter().copied(), ADD_CHUNK_SIZE, false)
+                .await?;
+            report.added = result.added_item_ids.len();
+        }
+        Ok(report)
+    }
+
+    /// Creates a private playlist, see `create_with_visibility` for public ones.
+    pub async fn create(&self, title: &str, description: &str) -> ClientResult<Playlist> {
+        self.create_with_visibility(title, description, false).await
+    }
+
+    /// Creates a playlist that's visible on the user's profile when `public` is set.
+    pub async fn create_with_visibility(
+        &self,
+        title: &str,
+        description: &str,
+        public: bool,
+    ) -> ClientResult<Playlist> {
+        let (playlist, _) = self.create_playlist(title, description, public).await?;
+        Ok(playlist)
+    }
+
+    /// Same as `create`, also returning the etag of the new playlist, which saves fetching it
+    /// before changing the playlist. Fails with `ClientError::ParseEtag` when Tidal doesn't send
+    /// one.
+    pub async fn create_with_etag(
+        &self,
+        title: &str,
+        description: &str,
+    ) -> ClientResult<(Playlist, String)> {
+        let (playlist, etag) = self.create_playlist(title, description, false).await?;
+        Ok((playlist, etag.ok_or(ClientError::ParseEtag)?))
+    }
+
+    async fn create_playlist(
+        &self,
+        title: &str,
+        description: &str,
+        public: bool,
+    ) -> ClientResult<(Playlist, Option<String>)> {
+        let user_id = self.0.user_id();
         let url = format!("/users/{}/playlists", user_id);
-        println!("URL: {:?}", url);
+        let public = public.to_string();
         let mut form: HashMap<&str, &str> = HashMap::new();
         form.insert("title", title);
         form.insert("description", description);
-        let result = self.0.post(&url, &form, None).await?;
-        Tidal::convert_result::<Playlist>(&result)
+        form.insert("public", &public);
+        let (result, etag) = self.0.post_with_etag(&url, &form, None).await?;
+        Ok((Tidal::convert_result::<Playlist>(&result)?, etag))
+    }
+
+    /// Changes the title and/or description of a playlist. When both are `None` the playlist is
+    /// returned unchanged.
+    pub async fn update(
+        &self,
+        id: &str,
+        title: Option<&str>,
+        description: Option<&str>,
+    ) -> ClientResult<Playlist> {
+        if title.is_none() && description.is_none() {
+            return self.get(id).await;
+        }
+
+        let url = format!("/playlists/{}", playlist_id(id)?);
+
+        // Get etag for the Playlist to be allowed to update the Playlist
+        let etag: String = self.0.etag(&url).await?;
+
+        let mut form: HashMap<&str, &str> = HashMap::new();
+        if let Some(title) = title {
+            form.insert("title", title);
+        }
+        if let Some(description) = description {
+            form.insert("description", description);
+        }
+        self.0.post(&url, &form, Some(etag)).await?;
+
+        // Get updated Playlist
+        self.get(id).await
+    }
+
+    /// Moves the item at `from_index` in front of the item at `to_index`. Nothing is modified when
+    /// both indices are the same, out of range indices are rejected by Tidal.
+    pub async fn move_item(
+        &self,
+        id: &str,
+        from_index: u32,
+        to_index: u32,
+    ) -> ClientResult<Playlist> {
+        if from_index == to_index {
+            return self.get(id).await;
+        }
+
+        let url = format!("/playlists/{}/items", playlist_id(id)?);
+
+        // Get etag for the Playlist to be allowed to update the Playlist
+        let etag: String = self.0.etag(&url).await?;
+
+        let url = format!("{}/{}/before/{}", url, from_index, to_index);
+        self.0.post(&url, &HashMap::new(), Some(etag)).await?;
+
+        // Get updated Playlist
+        self.get(id).await
+    }
+
+    /// Removes the items at the given indices from a playlist.
+    pub async fn remove_items(&self, id: &str, indices: &[u32]) -> ClientResult<()> {
+        let url = format!("/playlists/{}/items", playlist_id(id)?);
+
+        // Get etag for the Playlist to be allowed to update the Playlist
+        let etag: String = self.0.etag(&url).await?;
+
+        let indices: Vec<String> = indices.iter().map(ToString::to_string).collect();
+        let url = format!("{}/{}", url, indices.join(","));
+        self.0.delete(&url, Some(etag)).await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: &str) -> ClientResult<()> {
+        let url = format!("/playlists/{}", playlist_id(id)?);
+
+        // Get etag for the Playlist to be allowed to delete the Playlist
+        let etag: String = self.0.etag(&url).await?;
+
+        self.0.delete(&url, Some(etag)).await?;
+        Ok(())
     }
 
     pub async fn add_tracks(
         &self,
         id: &str,
-        tracks: Vec<Track>,
+        tracks: impl IntoIterator<Item = impl Into<TrackId>>,
         add_dupes: bool,
     ) -> ClientResult<Playlist> {
-        let url = format!("/playlists/{}/items", id);
+        let track_ids = track_ids(tracks)?;
+
+        // Submit request to add the Tracks to the Playlist
+        self.add_track_ids(id, &track_ids, add_dupes).await?;
+
+        // Get updated Playlist
+        self.0.playlist(id).await
+    }
+
+    /// Same as `add_tracks` but returns which tracks were added instead of reloading the playlist.
+    pub async fn add_tracks_detailed(
+        &self,
+        id: &str,
+        tracks: impl IntoIterator<Item = impl Into<TrackId>>,
+        add_dupes: bool,
+    ) -> ClientResult<AddTracksResult> {
+        let track_ids = track_ids(tracks)?;
+
+        self.add_track_ids(id, &track_ids, add_dupes).await
+    }
+
+    /// Same as `add_tracks_detailed` for more tracks than Tidal accepts at once, the tracks are
+    /// added in requests of up to `chunk_size` tracks. The playlist's etag is only fetched again
+    /// when Tidal doesn't answer an addition with the new one.
+    pub async fn add_tracks_chunked(
+        &self,
+        id: &str,
+        tracks: impl IntoIterator<Item = impl Into<TrackId>>,
+        chunk_size: usize,
+        add_dupes: bool,
+    ) -> ClientResult<AddTracksResult> {
+        let track_ids = track_ids(tracks)?;
+
+        let url = format!("/playlists/{}/items", playlist_id(id)?);
+        let mut result = AddTracksResult::default();
+        let mut etag: Option<String> = None;
+        for chunk in track_ids.chunks(chunk_size.max(1)) {
+            // Every addition changes the playlist, so the previous etag can't be reused
+            let current = match etag.take() {
+                Some(etag) => etag,
+                None => self.0.etag(&url).await?,
+            };
+            let (added, new_etag) = self.post_track_ids(&url, chunk, add_dupes, current).await?;
+            result.last_updated = added.last_updated;
+            result.added_item_ids.extend(added.added_item_ids);
+            etag = new_etag;
+        }
+        Ok(result)
+    }
+
+    pub(crate) async fn add_track_ids(
+        &self,
+        id: &str,
+        track_ids: &[u32],
+        add_dupes: bool,
+    ) -> ClientResult<AddTracksResult> {
+        let url = format!("/playlists/{}/items", playlist_id(id)?);
 
         // Get etag for the Playlist to be allowed to update the Playlist
         let etag: String = self.0.etag(&url).await?;
+        let (result, _) = self.post_track_ids(&url, track_ids, add_dupes, etag).await?;
+        Ok(result)
+    }
 
-        // Convert the list of Tracks to a String with comma separated Track IDs
-        let track_ids: Vec<String> = tracks
-            .iter()
-            .map(|track| track.id.expect("Track struct must have an ID").to_string())
-            .collect();
+    // Adds the tracks using `etag`, returns the etag of the changed playlist when Tidal sends it
+    async fn post_track_ids(
+        &self,
+        url: &str,
+        track_ids: &[u32],
+        add_dupes: bool,
+        etag: String,
+    ) -> ClientResult<(AddTracksResult, Option<String>)> {
+        // Convert the list of Track IDs to a comma separated String
+        let track_ids: Vec<String> = track_ids.iter().map(ToString::to_string).collect();
         let track_ids: String = track_ids.join(",");
 
         let on_dupes: String = if add_dupes {
@@ -72,11 +427,33 @@ impl Playlists<'_> {
         form.insert("trackIds", &track_ids);
         form.insert("onDupes", &on_dupes);
 
-        // Submit request to add the Tracks to the Playlist
-        self.0.post(&url, &form, Some(etag)).await?;
+        let (result, etag) = self.0.post_with_etag(url, &form, Some(etag)).await?;
+        Ok((Tidal::convert_result::<AddTracksResult>(&result)?, etag))
+    }
 
-        // Get updated Playlist
-        self.0.playlist(id).await
+    /// Folders at the top level of the user's collection.
+    pub async fn folders(&self) -> ClientResult<Vec<PlaylistFolder>> {
+        self.folder_items("root", "FOLDER").await
+    }
+
+    /// Playlists in the folder `folder_id` of the user's collection, `root` for the playlists
+    /// outside of any folder.
+    pub async fn folder_contents(&self, folder_id: &str) -> ClientResult<Vec<Playlist>> {
+        self.folder_items(folder_id.trim(), "PLAYLIST").await
+    }
+
+    async fn folder_items<T: DeserializeOwned>(
+        &self,
+        folder_id: &str,
+        include_only: &str,
+    ) -> ClientResult<Vec<T>> {
+        let url = "/my-collection/playlists/folders";
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert("folderId".to_owned(), folder_id.to_owned());
+        params.insert("includeOnly".to_owned(), include_only.to_owned());
+        let result = self.0.get(&url, &mut params).await?;
+        let items = Tidal::convert_result::<RawFolderItems<T>>(&result)?.items;
+        Ok(items.into_iter().map(|item| item.data).collect())
     }
 
     pub async fn user_playlists(&self) -> ClientResult<Vec<Playlist>> {
@@ -92,6 +469,7 @@ impl Playlists<'_> {
 mod tests {
     use super::*;
     use crate::client::tests::{client, mock_request_success_from_file};
+    use crate::model::playlist::CreatorType;
     use mockito::{mock, Matcher};
 
     #[tokio::test]
@@ -115,6 +493,86 @@ mod tests {
         };
         assert_eq!(result.uuid, expected_result.uuid);
         assert_eq!(result.title, expected_result.title);
+        assert_eq!(result.creator_type(), Some(CreatorType::Editorial));
+    }
+
+    #[tokio::test]
+    async fn get_trims_id() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/playlist.json",
+        );
+
+        let result: Playlist = client()
+            .playlists()
+            .get(" 7ce7df87-6d37-4465-80db-84535a4e44a4 ")
+            .await
+            .unwrap();
+        assert_eq!(result.uuid, Some("7ce7df87-6d37-4465-80db-84535a4e44a4".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn get_invalid_id() {
+        let result = client().playlists().get("7ce7df87-6d37-4465-80db").await;
+        assert!(matches!(result, Err(ClientError::InvalidId(_))));
+    }
+
+    #[tokio::test]
+    async fn mutations_invalid_id() {
+        let client = client();
+        let id = "../users/1/playlists";
+        let result = client.playlists().delete(id).await;
+        assert!(matches!(result, Err(ClientError::InvalidId(_))));
+        let result = client.playlists().remove_items(id, &[0]).await;
+        assert!(matches!(result, Err(ClientError::InvalidId(_))));
+        let result = client.playlists().add_tracks(id, vec![79914999], false).await;
+        assert!(matches!(result, Err(ClientError::InvalidId(_))));
+    }
+
+    #[tokio::test]
+    async fn get_many() {
+        let ids = [
+            "3b1c1d8a-0a3c-4b8e-9c3e-0f3e2c1a7b01",
+            "3b1c1d8a-0a3c-4b8e-9c3e-0f3e2c1a7b02",
+            "3b1c1d8a-0a3c-4b8e-9c3e-0f3e2c1a7b03",
+        ];
+        let _mocks: Vec<mockito::Mock> = ids
+            .iter()
+            .map(|id| {
+                mock("GET", format!("/playlists/{}", id).as_str())
+                    .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+                    .with_status(200)
+                    .with_body(format!(r#"{{"uuid": "{}"}}"#, id))
+                    .create()
+            })
+            .collect();
+
+        let result: Vec<Playlist> = client().playlists().get_many(&ids).await.unwrap();
+        let uuids: Vec<&str> = result.iter().filter_map(|p| p.uuid.as_deref()).collect();
+        assert_eq!(uuids, ids);
+    }
+
+    #[tokio::test]
+    async fn search_page() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/playlists",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "metalcore".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+                Matcher::UrlEncoded("offset".into(), "10".into()),
+            ],
+            "tests/files/search_playlists.json",
+        );
+
+        let result: TidalItems<Playlist> =
+            client().playlists().search_page("metalcore", None, Some(10)).await.unwrap();
+
+        assert_eq!(result.items.len(), 10);
+        assert_eq!(result.total_number_of_items, Some(13));
     }
 
     #[tokio::test]
@@ -136,6 +594,194 @@ mod tests {
         assert_eq!(result.description.unwrap(), "some desc".to_string());
     }
 
+    #[tokio::test]
+    async fn create_with_etag() {
+        let _mock = mock("POST", "/users/1234/playlists")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(200)
+            .with_header("etag", "\"1600273268158\"")
+            .with_body_from_file("tests/files/create_playlist.json")
+            .create();
+
+        let (playlist, etag) = client()
+            .playlists()
+            .create_with_etag("something", "some desc")
+            .await
+            .unwrap();
+
+        assert_eq!(playlist.title, Some("something".to_owned()));
+        assert_eq!(etag, "\"1600273268158\"");
+    }
+
+    #[tokio::test]
+    async fn create_with_visibility() {
+        let mock_create = mock("POST", "/users/1234/playlists")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_body(Matcher::Regex("public=true".to_owned()))
+            .with_status(200)
+            .with_body_from_file("tests/files/create_public_playlist.json")
+            .create();
+
+        let result: Playlist = client()
+            .playlists()
+            .create_with_visibility("something public", "some desc", true)
+            .await
+            .unwrap();
+
+        mock_create.assert();
+        assert_eq!(result.title.unwrap(), "something public".to_string());
+        assert_eq!(result.public_playlist, Some(true));
+    }
+
+    #[tokio::test]
+    async fn update() {
+        let _mock_playlist = mock(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .with_header("etag", "123457689")
+        .with_body_from_file("tests/files/playlist.json")
+        .create();
+
+        let mock_update_playlist = mock("POST", "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", "123457689")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex("title=new\\+title".to_owned()),
+                Matcher::Regex("description=new\\+description".to_owned()),
+            ]))
+            .with_body("")
+            .create();
+
+        let _result: Playlist = client()
+            .playlists()
+            .update(
+                "7ce7df87-6d37-4465-80db-84535a4e44a4",
+                Some("new title"),
+                Some("new description"),
+            )
+            .await
+            .unwrap();
+        mock_update_playlist.assert();
+    }
+
+    #[tokio::test]
+    async fn update_without_changes() {
+        let _mock_playlist = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/playlist.json",
+        );
+        let mock_update_playlist =
+            mock("POST", "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4").expect(0).create();
+
+        let result: Playlist = client()
+            .playlists()
+            .update("7ce7df87-6d37-4465-80db-84535a4e44a4", None, None)
+            .await
+            .unwrap();
+        assert_eq!(result.title, Some("Metal - TIDAL Masters".to_owned()));
+        mock_update_playlist.assert();
+    }
+
+    #[tokio::test]
+    async fn move_item() {
+        let _mock_reload_playlist = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/playlist.json",
+        );
+        let _mock_etag_req = mock(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .with_body("")
+        .with_header("etag", "123457689")
+        .create();
+        let mock_move_item = mock(
+            "POST",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items/3/before/1",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .match_header("if-none-match", "123457689")
+        .with_body("")
+        .create();
+
+        let _result: Playlist = client()
+            .playlists()
+            .move_item("7ce7df87-6d37-4465-80db-84535a4e44a4", 3, 1)
+            .await
+            .unwrap();
+        mock_move_item.assert();
+    }
+
+    #[tokio::test]
+    async fn delete() {
+        let _mock_etag_req = mock("GET", "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_header("etag", "123457689")
+            .with_body_from_file("tests/files/playlist.json")
+            .create();
+        let mock_delete_playlist = mock("DELETE", "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", "123457689")
+            .with_status(204)
+            .create();
+
+        let result = client()
+            .playlists()
+            .delete("7ce7df87-6d37-4465-80db-84535a4e44a4")
+            .await;
+        assert!(result.is_ok());
+        mock_delete_playlist.assert();
+    }
+
+    #[tokio::test]
+    async fn folders() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/my-collection/playlists/folders",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("folderId".into(), "root".into()),
+                Matcher::UrlEncoded("includeOnly".into(), "FOLDER".into()),
+            ],
+            "tests/files/playlist_folders.json",
+        );
+
+        let result: Vec<PlaylistFolder> = client().playlists().folders().await.unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].id, Some("5f7b2c6e-1d9a-4e3b-8c2f-6a4d0e9b1c37".to_owned()));
+        assert_eq!(result[0].name, Some("Metal".to_owned()));
+        assert_eq!(result[0].item_count, Some(2));
+        assert_eq!(result[1].created, Some("2021-03-14T09:12:45.123+0000".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn folder_contents() {
+        let folder_id = "5f7b2c6e-1d9a-4e3b-8c2f-6a4d0e9b1c37";
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/my-collection/playlists/folders",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("folderId".into(), folder_id.into()),
+                Matcher::UrlEncoded("includeOnly".into(), "PLAYLIST".into()),
+            ],
+            "tests/files/playlist_folder_contents.json",
+        );
+
+        let result: Vec<Playlist> =
+            client().playlists().folder_contents(folder_id).await.unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].uuid, Some("7ce7df87-6d37-4465-80db-84535a4e44a4".to_owned()));
+        assert_eq!(result[1].title, Some("roadtrip".to_owned()));
+    }
+
     #[tokio::test]
     async fn user_playlists() {
         let _mock = mock_request_success_from_file(
@@ -153,6 +799,11 @@ mod tests {
         };
         assert_eq!(result[0].uuid, expected_result.uuid);
         assert_eq!(result[0].title, expected_result.title);
+
+        let creator = result[0].creator.as_ref().unwrap();
+        assert_eq!(creator.id, Some(173393682));
+        assert_eq!(creator.name, Some("myuser".to_owned()));
+        assert_eq!(result[0].creator_type(), Some(CreatorType::User));
     }
 
     #[tokio::test]
@@ -176,6 +827,65 @@ mod tests {
         assert_eq!(result[0].title, expected_first_result.title);
     }
 
+    #[tokio::test]
+    async fn items() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "50".into()),
+                Matcher::UrlEncoded("offset".into(), "0".into()),
+            ],
+            "tests/files/playlist_items.json",
+        );
+
+        let result: Vec<PlaylistItem> = client()
+            .playlists()
+            .items("7ce7df87-6d37-4465-80db-84535a4e44a4")
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].item.id, Some(79915000));
+        assert_eq!(result[0].item.title, Some("Beyond Oblivion".to_owned()));
+        assert_eq!(result[0].date_added, Some("2020-09-19T16:50:02.000+0000".to_owned()));
+        assert_eq!(result[0].index, Some(0));
+        assert_eq!(result[1].index, Some(1));
+    }
+
+    #[tokio::test]
+    async fn entries() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/playlists/8edf5a89-fec4-4aa3-80ab-9e00a83633a2/items",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "50".into()),
+                Matcher::UrlEncoded("offset".into(), "0".into()),
+            ],
+            "tests/files/playlist_entries.json",
+        );
+
+        let result: Vec<PlaylistEntry> = client()
+            .playlists()
+            .entries("8edf5a89-fec4-4aa3-80ab-9e00a83633a2")
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        match &result[0] {
+            PlaylistEntry::Track(track) => assert_eq!(track.id, Some(79915000)),
+            entry => panic!("expected a track, got {:?}", entry),
+        }
+        match &result[1] {
+            PlaylistEntry::Video(video) => {
+                assert_eq!(video.id, Some(80431263));
+                assert_eq!(video.title, Some("The Sin and the Sentence".to_owned()));
+                assert_eq!(video.quality, Some("MP4_1080P".to_owned()));
+            }
+            entry => panic!("expected a video, got {:?}", entry),
+        }
+    }
+
     #[tokio::test]
     async fn add_tracks() {
         let _mock_reload_playlist = mock_request_success_from_file(
@@ -220,4 +930,230 @@ mod tests {
             .unwrap();
         mock_update_playlist.assert();
     }
+
+    #[tokio::test]
+    async fn add_tracks_detailed() {
+        let track_1 = Track {
+            id: Some(79914999),
+            ..Default::default()
+        };
+        let track_2 = Track {
+            id: Some(79915000),
+            ..Default::default()
+        };
+        let tracks = vec![track_1, track_2];
+
+        let _mock_etag_req = mock(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .with_body("")
+        .with_header("etag", "123457689")
+        .create();
+        let _mock_update_playlist = mock(
+            "POST",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .match_header("if-none-match", "123457689")
+        .with_body(r#"{ "lastUpdated": 1600273268158, "addedItemIds": [ 79914999 ] }"#)
+        .create();
+
+        let result: AddTracksResult = client()
+            .playlists()
+            .add_tracks_detailed("7ce7df87-6d37-4465-80db-84535a4e44a4", tracks, false)
+            .await
+            .unwrap();
+        assert_eq!(result.last_updated, 1600273268158);
+        assert_eq!(result.added_item_ids, vec![79914999]);
+    }
+
+    #[tokio::test]
+    async fn diff() {
+        let _mock_a = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/playlist_tracks.json",
+        );
+        let _mock_b = mock_request_success_from_file(
+            "GET",
+            "/playlists/8edf5a89-fec4-4aa3-80ab-9e00a83633a2/tracks",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/playlist_tracks_other.json",
+        );
+
+        let result: PlaylistDiff = client()
+            .playlists()
+            .diff(
+                "7ce7df87-6d37-4465-80db-84535a4e44a4",
+                "8edf5a89-fec4-4aa3-80ab-9e00a83633a2",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.only_in_a,
+            vec![147855096, 138803262, 138458225, 131916904, 128077293, 108651411, 109254415]
+        );
+        assert_eq!(result.only_in_b, vec![79914999, 79915000]);
+        assert_eq!(result.common, vec![108425901, 119894190, 126406929]);
+    }
+
+    #[tokio::test]
+    async fn all_tracks() {
+        let _mock_page_1 = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks",
+            vec![Matcher::UrlEncoded("offset".into(), "0".into())],
+            "tests/files/playlist_tracks_page_1.json",
+        );
+        let _mock_page_2 = mock_request_success_from_file(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/tracks",
+            vec![Matcher::UrlEncoded("offset".into(), "50".into())],
+            "tests/files/playlist_tracks_page_2.json",
+        );
+
+        let result: Vec<Track> = client()
+            .playlists()
+            .all_tracks("7ce7df87-6d37-4465-80db-84535a4e44a4")
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 62);
+    }
+
+    #[tokio::test]
+    async fn add_tracks_missing_id() {
+        let mock_add = mock("POST", "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items")
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create();
+
+        let tracks = vec![Track { id: Some(79914999), ..Default::default() }, Track::default()];
+        let result = client()
+            .playlists()
+            .add_tracks_detailed("7ce7df87-6d37-4465-80db-84535a4e44a4", tracks, false)
+            .await;
+
+        assert!(matches!(result, Err(ClientError::MissingField("id"))));
+        mock_add.assert();
+    }
+
+    #[test]
+    fn track_ids_from_tracks_and_ids() {
+        let track = Track { id: Some(79914999), ..Default::default() };
+        assert_eq!(track_ids(vec![&track]).unwrap(), vec![79914999]);
+        assert_eq!(track_ids(vec![79915000]).unwrap(), vec![79915000]);
+    }
+
+    #[tokio::test]
+    async fn add_tracks_chunked() {
+        let url = "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items";
+        let mock_etag_req = mock("GET", url)
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_body("")
+            .with_header("etag", "1")
+            .expect(1)
+            .create();
+        let mock_adds: Vec<mockito::Mock> = [(1, "1", "2"), (51, "2", "3"), (101, "3", "4")]
+            .iter()
+            .map(|(first_id, etag, new_etag)| {
+                mock("POST", url)
+                    .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+                    .match_header("if-none-match", *etag)
+                    .match_body(Matcher::Regex(format!("trackIds={}%2C", first_id)))
+                    .with_header("etag", new_etag)
+                    .with_body(format!(
+                        r#"{{"lastUpdated": {}, "addedItemIds": [{}]}}"#,
+                        new_etag, first_id
+                    ))
+                    .create()
+            })
+            .collect();
+
+        let tracks: Vec<Track> =
+            (1..=120).map(|id| Track { id: Some(id), ..Default::default() }).collect();
+        let result: AddTracksResult = client()
+            .playlists()
+            .add_tracks_chunked("7ce7df87-6d37-4465-80db-84535a4e44a4", tracks, 50, false)
+            .await
+            .unwrap();
+
+        mock_etag_req.assert();
+        for mock_add in &mock_adds {
+            mock_add.assert();
+        }
+        assert_eq!(result.last_updated, 4);
+        assert_eq!(result.added_item_ids, vec![1, 51, 101]);
+    }
+
+    #[tokio::test]
+    async fn sync_into() {
+        let url = "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items";
+        let _mock_source = mock_request_success_from_file(
+            "GET",
+            "/playlists/8edf5a89-fec4-4aa3-80ab-9e00a83633a2/tracks",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/playlist_tracks_other.json",
+        );
+        // Created before the etag mocks, which would match this request as well
+        let _mock_target = mock_request_success_from_file(
+            "GET",
+            url,
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "50".into()),
+                Matcher::UrlEncoded("offset".into(), "0".into()),
+            ],
+            "tests/files/playlist_sync_target_items.json",
+        );
+        // The removal changes the playlist, the addition has to use the etag fetched after it
+        let mock_etag_reqs: Vec<mockito::Mock> = ["123457689", "123457690"]
+            .iter()
+            .map(|etag| {
+                mock("GET", url)
+                    .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+                    .with_body("")
+                    .with_header("etag", etag)
+                    .expect(1)
+                    .create()
+            })
+            .collect();
+        // The video at position 1 is kept
+        let mock_remove = mock("DELETE", format!("{}/0,3", url).as_str())
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", "123457689")
+            .with_body("")
+            .create();
+        let mock_add = mock("POST", url)
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_header("if-none-match", "123457690")
+            .match_body(Matcher::Regex(
+                "trackIds=119894190%2C126406929%2C79914999%2C79915000".to_owned(),
+            ))
+            // One of the four tracks isn't added
+            .with_body(
+                r#"{ "lastUpdated": 1600273268158, "addedItemIds": [ 119894190, 79914999, 79915000 ] }"#,
+            )
+            .create();
+
+        let report: SyncReport = client()
+            .playlists()
+            .sync_into(
+                "8edf5a89-fec4-4aa3-80ab-9e00a83633a2",
+                "7ce7df87-6d37-4465-80db-84535a4e44a4",
+                true,
+            )
+            .await
+            .unwrap();
+
+        for mock_etag_req in &mock_etag_reqs {
+            mock_etag_req.assert();
+        }
+        mock_remove.assert();
+        mock_add.assert();
+        assert_eq!(report, SyncReport { added: 3, removed: 2 });
+    }
 }
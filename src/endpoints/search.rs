@@ -1,28 +1,180 @@
 //! Endpoint functions related to search
 
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
 use std::collections::HashMap;
 
-use crate::client::{ClientResult, Tidal, TidalSearch};
+use crate::client::{
+    pagination_params, search_term, ClientResult, SearchItem, Tidal, TidalItems, TidalSearch,
+};
+use crate::model::album::Album;
+use crate::model::artist::Artist;
+use crate::model::playlist::Playlist;
+use crate::model::track::Track;
+use crate::model::ModelType;
 
 pub struct Search<'a>(pub &'a Tidal);
 
+/// Optional parameters of `Search::find_with_options`.
+#[derive(Clone, Debug, Default)]
+pub struct SearchOptions {
+    /// Restricts the result buckets, types that can't be searched are ignored.
+    pub types: Option<Vec<ModelType>>,
+    pub offset: Option<u16>,
+    pub include_contributors: Option<bool>,
+}
+
+// Suggested terms come with the highlighted parts, only the terms are kept
+#[derive(Debug, Deserialize)]
+struct Suggestions {
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Suggestion {
+    query: String,
+}
+
 impl Search<'_> {
-    pub async fn find(&self, term: &str, limit: Option<u16>) -> ClientResult<TidalSearch> {
+    pub async fn find(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<TidalSearch> {
+        let options = SearchOptions { offset, ..Default::default() };
+        self.find_with_options(term, limit, options).await
+    }
+
+    pub async fn find_with_options(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        options: SearchOptions,
+    ) -> ClientResult<TidalSearch> {
         let url = "/search";
-        let limit = if let Some(limit) = limit { limit } else { 10 };
+        let offset = options.offset.map(u32::from);
+        let mut params =
+            pagination_params(limit, offset, self.0.default_limit(), self.0.max_limit())?;
+        params.insert("query".to_owned(), search_term(term)?.to_owned());
+        if let Some(types) = options.types {
+            let types: Vec<&str> = types.iter().filter_map(ModelType::search_type).collect();
+            params.insert("types".to_owned(), types.join(","));
+        }
+        if let Some(include_contributors) = options.include_contributors {
+            params.insert("includeContributors".to_owned(), include_contributors.to_string());
+        }
+        let result = self.0.get_search(&url, &mut params).await?;
+        Tidal::convert_result::<TidalSearch>(&result)
+    }
+
+    pub async fn artists(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<Vec<Artist>> {
+        self.typed("/search/artists", term, limit, offset).await
+    }
+
+    pub async fn albums(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<Vec<Album>> {
+        self.typed("/search/albums", term, limit, offset).await
+    }
+
+    pub async fn playlists(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<Vec<Playlist>> {
+        self.typed("/search/playlists", term, limit, offset).await
+    }
+
+    /// Same as `playlists`, together with the total number of matching playlists for paging.
+    pub async fn playlists_page(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<TidalItems<Playlist>> {
+        self.typed_page("/search/playlists", term, limit, offset).await
+    }
+
+    pub async fn tracks(
+        &self,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<Vec<Track>> {
+        self.typed("/search/tracks", term, limit, offset).await
+    }
+
+    /// The single best match for `term`, `None` when nothing matches.
+    pub async fn top_hit(&self, term: &str) -> ClientResult<Option<SearchItem>> {
+        let url = "/search/topHits";
         let mut params: HashMap<String, String> = HashMap::new();
-        params.insert("query".to_owned(), term.to_owned());
-        params.insert("limit".to_owned(), limit.to_string());
+        params.insert("query".to_owned(), search_term(term)?.to_owned());
         let result = self.0.get(&url, &mut params).await?;
-        Tidal::convert_result::<TidalSearch>(&result)
+        if result.trim().is_empty() {
+            return Ok(None);
+        }
+        Tidal::convert_result::<Option<SearchItem>>(&result)
+    }
+
+    /// Completions of a partially typed search term, e.g. for search-as-you-type. A lot cheaper
+    /// than searching for every keystroke.
+    pub async fn suggest(&self, partial: &str) -> ClientResult<Vec<String>> {
+        let url = "/suggestions";
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert("query".to_owned(), search_term(partial)?.to_owned());
+        let result = self.0.get(&url, &mut params).await?;
+        let suggestions = Tidal::convert_result::<Suggestions>(&result)?.suggestions;
+        Ok(suggestions.into_iter().map(|suggestion| suggestion.query).collect())
+    }
+
+    // Typed search endpoints only return the requested type, keeping the payload small
+    async fn typed<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<Vec<T>> {
+        Ok(self.typed_page(url, term, limit, offset).await?.items)
+    }
+
+    async fn typed_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        term: &str,
+        limit: Option<u16>,
+        offset: Option<u16>,
+    ) -> ClientResult<TidalItems<T>> {
+        let mut params = pagination_params(
+            limit,
+            offset.map(u32::from),
+            self.0.default_limit(),
+            self.0.max_limit(),
+        )?;
+        params.insert("query".to_owned(), search_term(term)?.to_owned());
+        let result = self.0.get_search(&url, &mut params).await?;
+        Tidal::convert_result::<TidalItems<T>>(&result)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::client::tests::{client, mock_request_success_from_file};
-    use mockito::Matcher;
+    use crate::client::tests::{client, credential, mock_request_success_from_file};
+    use crate::client::{ClientError, TidalBuilder};
+    use std::time::Duration;
+    use mockito::{mock, Matcher};
 
     #[tokio::test]
     async fn find() {
@@ -38,11 +190,341 @@ mod tests {
         )
         .create();
 
-        let result: TidalSearch = client().searches().find("trivium", None).await.unwrap();
+        let result: TidalSearch = client().searches().find("trivium", None, None).await.unwrap();
 
         assert_eq!(result.artists.items.len(), 10);
         assert_eq!(result.albums.items.len(), 10);
         assert_eq!(result.tracks.items.len(), 10);
         assert_eq!(result.playlists.items.len(), 10);
+        assert!(result.contributors.items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn find_top_hit() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivium band".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/search.json",
+        );
+
+        let result: TidalSearch =
+            client().searches().find("trivium band", None, None).await.unwrap();
+
+        match &result.top_hit {
+            Some(SearchItem::Artist(artist)) => assert_eq!(artist.id, Some(37312)),
+            _ => panic!("expected an artist, got {:?}", result.top_hit),
+        }
+    }
+
+    #[tokio::test]
+    async fn find_empty_term() {
+        let mock = mock("GET", "/search").match_query(Matcher::Any).expect(0).create();
+
+        let client = client();
+        let empty = client.searches().find("", None, None).await;
+        let blank = client.searches().find("   ", None, None).await;
+
+        assert!(matches!(empty, Err(ClientError::EmptyQuery)));
+        assert!(matches!(blank, Err(ClientError::EmptyQuery)));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn find_trims_term() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "heaven shall burn".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/search.json",
+        );
+
+        let result = client().searches().find(" heaven shall burn ", None, None).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn suggest() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/suggestions",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivi".into()),
+            ],
+            "tests/files/search_suggestions.json",
+        );
+
+        let result: Vec<String> = client().searches().suggest("trivi").await.unwrap();
+        assert_eq!(
+            result,
+            vec!["trivium", "trivium in waves", "trivium the sin and the sentence", "triviul"]
+        );
+    }
+
+    #[tokio::test]
+    async fn find_default_limit() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "in waves".into()),
+                Matcher::UrlEncoded("limit".into(), "50".into()),
+            ],
+            "tests/files/search.json",
+        );
+
+        let client = TidalBuilder::new().default_limit(50).build(credential()).unwrap();
+        let result: TidalSearch = client.searches().find("in waves", None, None).await.unwrap();
+
+        assert_eq!(result.artists.items.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn find_max_limit() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "architects".into()),
+                Matcher::UrlEncoded("limit".into(), "50".into()),
+            ],
+            "tests/files/search.json",
+        );
+
+        let result: TidalSearch =
+            client().searches().find("architects", Some(5000), None).await.unwrap();
+
+        assert_eq!(result.artists.items.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn find_cached() {
+        let mock = mock_request_success_from_file(
+            "GET",
+            "/search",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "the sin".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/search.json",
+        )
+        .expect(1);
+
+        let client = TidalBuilder::new()
+            .search_cache(10, Duration::from_secs(60))
+            .build(credential())
+            .unwrap();
+        let first: TidalSearch = client.searches().find("the sin", None, None).await.unwrap();
+        let second: TidalSearch = client.searches().find("the sin", None, None).await.unwrap();
+
+        assert_eq!(first.artists.items.len(), 10);
+        assert_eq!(second.artists.items.len(), 10);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn find_with_options() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivium".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+                Matcher::UrlEncoded("offset".into(), "10".into()),
+                Matcher::UrlEncoded("types".into(), "ARTISTS,TRACKS".into()),
+                Matcher::UrlEncoded("includeContributors".into(), "true".into()),
+            ],
+            "tests/files/search.json",
+        );
+
+        let options = SearchOptions {
+            types: Some(vec![ModelType::Artist, ModelType::Track]),
+            offset: Some(10),
+            include_contributors: Some(true),
+        };
+        let result: TidalSearch = client()
+            .searches()
+            .find_with_options("trivium", None, options)
+            .await
+            .unwrap();
+
+        assert_eq!(result.artists.items.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn find_with_contributors() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search",
+            vec![
+                Matcher::UrlEncoded("query".into(), "matt heafy".into()),
+                Matcher::UrlEncoded("includeContributors".into(), "true".into()),
+            ],
+            "tests/files/search_contributors.json",
+        );
+
+        let options = SearchOptions { include_contributors: Some(true), ..Default::default() };
+        let result: TidalSearch = client()
+            .searches()
+            .find_with_options("matt heafy", None, options)
+            .await
+            .unwrap();
+
+        let contributors = &result.contributors.items;
+        assert_eq!(contributors.len(), 2);
+        assert_eq!(contributors[0].name, Some("Matt Heafy".to_owned()));
+        assert_eq!(contributors[0].role, Some("Producer".to_owned()));
+        assert_eq!(contributors[1].id, Some(5321866));
+        assert_eq!(contributors[1].picture, None);
+    }
+
+    #[tokio::test]
+    async fn artists() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/artists",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivium".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/search_artists.json",
+        );
+
+        let result: Vec<Artist> = client().searches().artists("trivium", None, None).await.unwrap();
+
+        assert_eq!(result.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn artists_offset() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/artists",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivium".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+                Matcher::UrlEncoded("offset".into(), "10".into()),
+            ],
+            "tests/files/search_artists.json",
+        );
+
+        let result: Vec<Artist> =
+            client().searches().artists("trivium", None, Some(10)).await.unwrap();
+
+        assert_eq!(result.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn albums() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/albums",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivium".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/search_albums.json",
+        );
+
+        let result: Vec<Album> = client().searches().albums("trivium", None, None).await.unwrap();
+
+        assert_eq!(result.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn playlists() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/playlists",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivium".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/search_playlists.json",
+        );
+
+        let result: Vec<Playlist> =
+            client().searches().playlists("trivium", None, None).await.unwrap();
+
+        assert_eq!(result.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn tracks() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/tracks",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "trivium".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/search_tracks.json",
+        );
+
+        let result: Vec<Track> = client().searches().tracks("trivium", None, None).await.unwrap();
+
+        assert_eq!(result.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn top_hit() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/topHits",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "in waves".into()),
+            ],
+            "tests/files/search_top_hit.json",
+        );
+
+        let result = client().searches().top_hit("in waves").await.unwrap();
+
+        match result {
+            Some(SearchItem::Track(track)) => assert_eq!(track.title, Some("In Waves".to_owned())),
+            _ => panic!("expected a track top hit, got {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn top_hit_video() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/search/topHits",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("query".into(), "the sin and the sentence video".into()),
+            ],
+            "tests/files/search_top_hit_video.json",
+        );
+
+        let result = client().searches().top_hit("the sin and the sentence video").await.unwrap();
+
+        match result {
+            Some(SearchItem::Video(video)) => {
+                assert_eq!(video.id, Some(80431263));
+                assert_eq!(video.quality, Some("MP4_1080P".to_owned()));
+            }
+            _ => panic!("expected a video top hit, got {:?}", result),
+        }
     }
 }
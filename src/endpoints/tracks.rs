@@ -1,33 +1,349 @@
-//! Endpoint functions related to playlists
+//! Endpoint functions related to tracks
 
-use crate::client::{ClientResult, Tidal};
+use futures_util::stream::{self, Stream};
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::client::{
+    numeric_id, pagination_params, ClientError, ClientResult, Tidal, TidalItems,
+};
+use crate::model::credit::TrackContributor;
+use crate::model::playback::{PlaybackInfo, StreamInfo, StreamManifest};
 use crate::model::track::Track;
+use crate::model::AudioQuality;
 
 pub struct Tracks<'a>(pub &'a Tidal);
 
+// Number of recently played track ids remembered by `radio_stream` to avoid repeats
+const RADIO_HISTORY: usize = 200;
+
+struct RadioState {
+    // Track the next batch is seeded from, `None` once the station ran out of new tracks
+    seed: Option<String>,
+    first_batch: bool,
+    queue: VecDeque<Track>,
+    history: VecDeque<u32>,
+}
+
+impl<'a> Tracks<'a> {
+    /// An endless station of tracks similar to the given one. The first batch is the track radio,
+    /// once a batch is played through the next one is fetched from the continuation of its last
+    /// track. Recently seen tracks are skipped and the stream ends after an error or when no new
+    /// tracks can be found.
+    pub fn radio_stream(&self, id: &str) -> impl Stream<Item = ClientResult<Track>> + 'a {
+        let tidal = self.0;
+        let state = RadioState {
+            seed: Some(id.to_owned()),
+            first_batch: true,
+            queue: VecDeque::new(),
+            history: VecDeque::new(),
+        };
+
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(track) = state.queue.pop_front() {
+                    return Some((Ok(track), state));
+                }
+
+                let seed = match state.seed.take() {
+                    Some(seed) => seed,
+                    None => return None,
+                };
+                let batch = if state.first_batch {
+                    tidal.tracks().radio(&seed, None).await
+                } else {
+                    tidal.tracks().continuation(&seed, None).await
+                };
+                state.first_batch = false;
+
+                let tracks = match batch {
+                    Ok(tracks) => tracks,
+                    Err(err) => return Some((Err(err), state)),
+                };
+                let last_id = tracks.last().and_then(|track| track.id);
+                for track in tracks {
+                    let id = match track.id {
+                        Some(id) if !state.history.contains(&id) => id,
+                        _ => continue,
+                    };
+                    if state.history.len() == RADIO_HISTORY {
+                        state.history.pop_front();
+                    }
+                    state.history.push_back(id);
+                    state.queue.push_back(track);
+                }
+                if !state.queue.is_empty() {
+                    state.seed = last_id.map(|id| id.to_string());
+                }
+            }
+        })
+    }
+}
+
 impl Tracks<'_> {
+    pub async fn get(&self, id: &str) -> ClientResult<Track> {
+        self.get_with(id, HashMap::new()).await
+    }
+
+    /// Same as `get`, sending `extra` as additional query parameters, e.g. for flags this crate
+    /// doesn't know about yet.
+    pub async fn get_with(
+        &self,
+        id: &str,
+        mut extra: HashMap<String, String>,
+    ) -> ClientResult<Track> {
+        let url = format!("/tracks/{}", numeric_id(id)?);
+        let result = self.0.get(&url, &mut extra).await?;
+        Tidal::convert_result::<Track>(&result)
+    }
+
+    /// Same as `get`, also returning the response as JSON to inspect fields `Track` doesn't
+    /// model.
+    pub async fn get_raw(&self, id: &str) -> ClientResult<(Track, serde_json::Value)> {
+        let url = format!("/tracks/{}", numeric_id(id)?);
+        let result = self.0.get(&url, &mut HashMap::new()).await?;
+        Tidal::convert_result_raw::<Track>(&result)
+    }
+
+    /// Fetches several tracks concurrently, at most `TidalBuilder::max_concurrency` at a time.
+    /// Tracks keep the order of `ids`. Ids Tidal doesn't know (404) are skipped so a single
+    /// removed track doesn't fail the whole batch, any other error is returned.
+    pub async fn get_many(&self, ids: &[&str]) -> ClientResult<Vec<Track>> {
+        let results = self.0.fan_out(ids, |id| self.get(id)).await;
+
+        let mut tracks = Vec::with_capacity(results.len());
+        for result in results {
+            match result {
+                Ok(track) => tracks.push(track),
+                Err(err) if err.is_not_found() => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(tracks)
+    }
+
+    /// Replaces the partial album some endpoints embed in tracks, e.g. search, with the full
+    /// album when it lacks a cover. Tracks without an album id are returned unchanged.
+    pub async fn with_full_album(&self, mut track: Track) -> ClientResult<Track> {
+        let album_id = match &track.album {
+            Some(album) if album.cover.is_none() => album.id,
+            _ => None,
+        };
+        if let Some(album_id) = album_id {
+            track.album = Some(self.0.albums().get(&album_id.to_string()).await?);
+        }
+        Ok(track)
+    }
+
+    /// Credits of the track, one entry per contributor and role.
+    pub async fn contributors(&self, id: &str) -> ClientResult<Vec<TrackContributor>> {
+        let url = format!("/tracks/{}/contributors", numeric_id(id)?);
+        self.0.get_all(&url, 100).await
+    }
+
     pub async fn search(&self, term: &str, limit: Option<u16>) -> ClientResult<Vec<Track>> {
-        let tracks = self.0.search(term, limit).await?.tracks.items;
+        self.0.searches().tracks(term, limit, None).await
+    }
+
+    /// Adds a track to each of the given playlists. The result of every playlist is returned,
+    /// in the same order as `playlist_ids`, so partial failures are visible.
+    pub async fn add_to_playlists(
+        &self,
+        track_id: u32,
+        playlist_ids: &[&str],
+        add_dupes: bool,
+    ) -> ClientResult<Vec<(String, ClientResult<()>)>> {
+        let results = self
+            .0
+            .fan_out(playlist_ids, |playlist_id| async move {
+                let result = self
+                    .0
+                    .playlists()
+                    .add_track_ids(playlist_id, &[track_id], add_dupes)
+                    .await
+                    .map(|_| ());
+                ((*playlist_id).to_owned(), result)
+            })
+            .await;
+        Ok(results)
+    }
+
+    /// Where to stream the track from. Requests the track's full asset
+    /// (`assetpresentation=FULL`) for streaming (`playbackmode=STREAM`) in the given
+    /// `audioquality`. Only the JSON manifests Tidal uses up to lossless quality are supported,
+    /// DASH manifests return `ClientError::UnsupportedManifest`.
+    pub async fn stream_url(&self, id: &str, quality: AudioQuality) -> ClientResult<StreamInfo> {
+        let url = format!("/tracks/{}/playbackinfopostpaywall", numeric_id(id)?);
+        let quality = serde_json::to_value(&quality)?;
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert("audioquality".to_owned(), quality.as_str().unwrap_or_default().to_owned());
+        params.insert("playbackmode".to_owned(), "STREAM".to_owned());
+        params.insert("assetpresentation".to_owned(), "FULL".to_owned());
+        let result = self.0.get(&url, &mut params).await?;
+        let info = Tidal::convert_result::<PlaybackInfo>(&result)?;
+
+        if info.manifest_mime_type != "application/vnd.tidal.bts" {
+            return Err(ClientError::UnsupportedManifest(info.manifest_mime_type));
+        }
+        let manifest = base64::decode(&info.manifest)
+            .map_err(|err| ClientError::UnsupportedManifest(err.to_string()))?;
+        let manifest: StreamManifest = serde_json::from_slice(&manifest)?;
+
+        Ok(StreamInfo {
+            track_id: info.track_id,
+            url: manifest.urls.first().cloned(),
+            urls: manifest.urls,
+            codec: manifest.codecs,
+            encryption_type: manifest.encryption_type,
+            sound_quality: info.audio_quality,
+        })
+    }
+
+    /// Reports that the track was played so it's taken into account for recommendations.
+    pub async fn report_play(&self, id: &str, quality: AudioQuality) -> ClientResult<()> {
+        let id = numeric_id(id)?;
+        let quality = serde_json::to_value(&quality)?;
+        let quality = quality.as_str().unwrap_or_default();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default()
+            .to_string();
+
+        let mut form: HashMap<&str, &str> = HashMap::new();
+        form.insert("trackId", id);
+        form.insert("audioQuality", quality);
+        form.insert("timestamp", &timestamp);
+        self.0.post("/play", &form, None).await?;
+        Ok(())
+    }
+
+    /// Tracks of Tidal's track radio (`/tracks/{id}/radio`). For the tracks of the track's
+    /// `TRACK_MIX` use `mix`, `continuation` combines both.
+    pub async fn radio(&self, id: &str, limit: Option<u16>) -> ClientResult<Vec<Track>> {
+        let url = format!("/tracks/{}/radio", numeric_id(id)?);
+        let mut params =
+            pagination_params(limit, None, self.0.default_limit(), self.0.max_limit())?;
+        let result = self.0.get(&url, &mut params).await?;
+        let tracks = Tidal::convert_result::<TidalItems<Track>>(&result)?.items;
         Ok(tracks)
     }
+
+    /// Tracks of the track's mix, i.e. the items of its `TRACK_MIX`. Fails with
+    /// `ClientError::MissingField` when Tidal has no mix for the track, see `continuation` for a
+    /// fallback to the track radio.
+    ///
+    /// It isn't named `radio` since that name already fetches the `/tracks/{id}/radio` endpoint,
+    /// which returns different tracks than the mix.
+    pub async fn mix(&self, id: &str) -> ClientResult<Vec<Track>> {
+        let track = self.get(id).await?;
+        let mix_id = track
+            .mixes
+            .as_ref()
+            .and_then(|mixes| mixes.get("TRACK_MIX"))
+            .ok_or(ClientError::MissingField("TRACK_MIX"))?;
+        self.0.mixes().get_items(mix_id).await
+    }
+
+    /// Tracks to keep playing after the given track. Uses the track's `TRACK_MIX` when it has
+    /// one and falls back to the track radio otherwise.
+    pub async fn continuation(&self, id: &str, limit: Option<u16>) -> ClientResult<Vec<Track>> {
+        let track = self.get(id).await?;
+        let mix_id = track
+            .mixes
+            .as_ref()
+            .and_then(|mixes| mixes.get("TRACK_MIX"));
+
+        let mix_id = match mix_id {
+            Some(mix_id) => mix_id,
+            None => return self.radio(id, limit).await,
+        };
+
+        self.0.mixes().items(mix_id, limit).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::client::tests::{client, mock_request_success_from_file};
-    use mockito::Matcher;
+    use futures_util::stream::StreamExt;
+    use mockito::{mock, Matcher};
+
+    #[tokio::test]
+    async fn get() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/track.json",
+        );
+
+        let result: Track = client().tracks().get("79914999").await.unwrap();
+        assert_eq!(result.title, Some("The Sin and the Sentence".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn contributors() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999/contributors",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "100".into()),
+                Matcher::UrlEncoded("offset".into(), "0".into()),
+            ],
+            "tests/files/track_contributors.json",
+        );
+
+        let result: Vec<TrackContributor> =
+            client().tracks().contributors("79914999").await.unwrap();
+        assert_eq!(result.len(), 4);
+        assert_eq!(result[0].name, Some("Matthew Heafy".to_owned()));
+        assert_eq!(result[0].role, Some("Composer".to_owned()));
+        assert_eq!(result[2].role, Some("Producer".to_owned()));
+        assert_eq!(result[3].role, Some("Mixing Engineer".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn get_many_skips_missing_tracks() {
+        let _mock_found = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/track.json",
+        );
+        let _mock_missing = mock("GET", "/tracks/1")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(404)
+            .with_body(r#"{"status": 404, "subStatus": 2001, "userMessage": "Track not found"}"#)
+            .create();
+        let _mock_other = mock("GET", "/tracks/79915000")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(200)
+            .with_body(r#"{"id": 79915000, "title": "Beyond Oblivion"}"#)
+            .create();
+
+        let result: Vec<Track> = client()
+            .tracks()
+            .get_many(&["79915000", "1", "79914999"])
+            .await
+            .unwrap();
+        let ids: Vec<Option<u32>> = result.iter().map(|track| track.id).collect();
+        assert_eq!(ids, vec![Some(79915000), Some(79914999)]);
+    }
 
     #[tokio::test]
     async fn search() {
         let _mock = mock_request_success_from_file(
             "GET",
-            "/search",
+            "/search/tracks",
             vec![
                 Matcher::UrlEncoded("countryCode".into(), "US".into()),
                 Matcher::UrlEncoded("query".into(), "trivium".into()),
             ],
-            "tests/files/search.json",
+            "tests/files/search_tracks.json",
         )
         .create();
 
@@ -35,4 +351,242 @@ mod tests {
 
         assert_eq!(result.len(), 10);
     }
+
+    #[tokio::test]
+    async fn mix() {
+        let _mock_track = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/track.json",
+        );
+        let mock_mix = mock_request_success_from_file(
+            "GET",
+            "/mixes/0017159e6a1f34ae3d981792d72ecf/items",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/mix_items.json",
+        );
+
+        let result: Vec<Track> = client().tracks().mix("79914999").await.unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].title, Some("Beyond Oblivion".to_owned()));
+        mock_mix.assert();
+    }
+
+    #[tokio::test]
+    async fn mix_missing() {
+        let _mock_track = mock("GET", "/tracks/79914985")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .with_status(200)
+            .with_body(r#"{"id": 79914985, "title": "Pillars of Serpents"}"#)
+            .create();
+
+        let result = client().tracks().mix("79914985").await;
+        assert!(matches!(result, Err(ClientError::MissingField("TRACK_MIX"))));
+    }
+
+    #[tokio::test]
+    async fn with_full_album() {
+        let mock_album = mock_request_success_from_file(
+            "GET",
+            "/albums/79914998",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/album.json",
+        );
+
+        let track: Track = serde_json::from_str(
+            r#"{"id": 79914999, "album": {"id": 79914998, "title": "The Sin and the Sentence"}}"#,
+        )
+        .unwrap();
+        let result: Track = client().tracks().with_full_album(track).await.unwrap();
+
+        let album = result.album.unwrap();
+        assert_eq!(album.cover, Some("579664d3-836e-4170-a46e-cbd2ed404f47".to_owned()));
+        mock_album.assert();
+    }
+
+    #[tokio::test]
+    async fn with_full_album_keeps_complete_album() {
+        let mock_album =
+            mock("GET", "/albums/79914983").match_query(Matcher::Any).expect(0).create();
+
+        let track: Track = serde_json::from_str(
+            r#"{"id": 79914982, "album": {"id": 79914983, "cover": "579664d3-836e-4170"}}"#,
+        )
+        .unwrap();
+        let result: Track = client().tracks().with_full_album(track).await.unwrap();
+
+        assert_eq!(result.album.unwrap().cover, Some("579664d3-836e-4170".to_owned()));
+        mock_album.assert();
+    }
+
+    #[tokio::test]
+    async fn continuation() {
+        let _mock_track = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/track.json",
+        );
+        let _mock_mix = mock_request_success_from_file(
+            "GET",
+            "/mixes/0017159e6a1f34ae3d981792d72ecf/items",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/mix_items.json",
+        );
+
+        let result: Vec<Track> = client().tracks().continuation("79914999", None).await.unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].title, Some("Beyond Oblivion".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn add_to_playlists() {
+        let _mock_etag_req = mock(
+            "GET",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .with_body("")
+        .with_header("etag", "123457689")
+        .create();
+        let mock_update_playlist = mock(
+            "POST",
+            "/playlists/7ce7df87-6d37-4465-80db-84535a4e44a4/items",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .match_header("if-none-match", "123457689")
+        .with_body(r#"{ "lastUpdated": 1600273268158, "addedItemIds": [ 79914999 ] }"#)
+        .create();
+        let _mock_missing_playlist = mock(
+            "GET",
+            "/playlists/8edf5a89-fec4-4aa3-80ab-9e00a83633a2/items",
+        )
+        .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+        .with_status(404)
+        .with_body(r#"{"status": 404, "subStatus": 2001, "userMessage": "Playlist not found"}"#)
+        .create();
+
+        let results = client()
+            .tracks()
+            .add_to_playlists(
+                79914999,
+                &[
+                    "7ce7df87-6d37-4465-80db-84535a4e44a4",
+                    "8edf5a89-fec4-4aa3-80ab-9e00a83633a2",
+                ],
+                false,
+            )
+            .await
+            .unwrap();
+
+        mock_update_playlist.assert();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "7ce7df87-6d37-4465-80db-84535a4e44a4");
+        assert!(results[0].1.is_ok());
+        assert_eq!(results[1].0, "8edf5a89-fec4-4aa3-80ab-9e00a83633a2");
+        assert!(results[1].1.is_err());
+    }
+
+    #[tokio::test]
+    async fn report_play() {
+        let mock_report = mock("POST", "/play")
+            .match_query(Matcher::UrlEncoded("countryCode".into(), "US".into()))
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex("trackId=79914999".to_owned()),
+                Matcher::Regex("audioQuality=LOSSLESS".to_owned()),
+                Matcher::Regex("timestamp=[0-9]+".to_owned()),
+            ]))
+            .with_body("")
+            .create();
+
+        client()
+            .tracks()
+            .report_play("79914999", AudioQuality::Lossless)
+            .await
+            .unwrap();
+        mock_report.assert();
+    }
+
+    #[tokio::test]
+    async fn radio_stream() {
+        let _mock_radio = mock_request_success_from_file(
+            "GET",
+            "/tracks/79915003/radio",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/track_radio.json",
+        );
+        let _mock_track = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999",
+            vec![Matcher::UrlEncoded("countryCode".into(), "US".into())],
+            "tests/files/track.json",
+        );
+        let _mock_mix = mock_request_success_from_file(
+            "GET",
+            "/mixes/0017159e6a1f34ae3d981792d72ecf/items",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("limit".into(), "10".into()),
+            ],
+            "tests/files/mix_items.json",
+        );
+
+        let client = client();
+        let titles: Vec<String> = client
+            .tracks()
+            .radio_stream("79915003")
+            .take(5)
+            .map(|track| track.unwrap().title.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            titles,
+            vec![
+                "Betrayer",
+                "The Wretchedness Inside",
+                "The Sin and the Sentence",
+                "Beyond Oblivion",
+                "Other Worlds",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_url() {
+        let _mock = mock_request_success_from_file(
+            "GET",
+            "/tracks/79914999/playbackinfopostpaywall",
+            vec![
+                Matcher::UrlEncoded("countryCode".into(), "US".into()),
+                Matcher::UrlEncoded("audioquality".into(), "LOSSLESS".into()),
+                Matcher::UrlEncoded("playbackmode".into(), "STREAM".into()),
+                Matcher::UrlEncoded("assetpresentation".into(), "FULL".into()),
+            ],
+            "tests/files/track_playback_info.json",
+        );
+
+        let result: StreamInfo = client()
+            .tracks()
+            .stream_url("79914999", AudioQuality::Lossless)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.url,
+            Some("https://sp-pr-cf.audio.tidal.com/mediatracks/CAEaKwgDEidmMjNhYTkzNjY0/0.flac".to_owned())
+        );
+        assert_eq!(result.codec, Some("flac".to_owned()));
+        assert_eq!(result.encryption_type, Some("NONE".to_owned()));
+        assert!(matches!(result.sound_quality, Some(AudioQuality::Lossless)));
+    }
 }
@@ -19,6 +19,9 @@
 //! session using a Tidal username and password.
 //! In order to authenticate a user your application needs an Application Token.
 //!
+//! Alternatively, `TidalCredentials::device_login` starts an OAuth device authorization where
+//! the user authorizes your application on `link.tidal.com` instead of sharing their password.
+//!
 //!
 //! ## How to get an Application Token
 //!
@@ -52,7 +55,7 @@
 //!     // Create a session using your user credentials.
 //!     let username = env::var("RSTIDAL_USERNAME").unwrap();
 //!     let password = env::var("RSTIDAL_PASSWORD").unwrap();
-//!     let credentials = credentials.create_session(&username, &password).await;
+//!     let credentials = credentials.try_create_session(&username, &password).await.unwrap();
 //!
 //!     // Use the credentials to start the client
 //!     let client = Tidal::new(credentials);
@@ -1,13 +1,21 @@
 // Use 3rd party
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDate, Utc};
+
+// Use built-in library
+use std::time::Duration;
+
 // Use local
 use crate::model::artist::Artist;
-use crate::model::{AudioMode, AudioQuality, ModelType};
+use crate::model::track::Track;
+use crate::model::{de_u32_flexible, image_url, is_past, AudioMode, AudioQuality, ModelType};
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Album {
+    #[serde(default, deserialize_with = "de_u32_flexible")]
     pub id: Option<u32>,
     pub title: Option<String>,
     pub duration: Option<u32>,
@@ -29,7 +37,91 @@ pub struct Album {
     pub popularity: Option<u8>,
     pub audio_quality: Option<AudioQuality>,
     pub audio_modes: Option<Vec<AudioMode>>,
+    /// The main artist of the album.
+    pub artist: Option<Artist>,
     pub artists: Option<Vec<Artist>>,
     #[serde(rename = "type")]
     pub _type: Option<ModelType>,
 }
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumReview {
+    pub source: Option<String>,
+    pub last_updated: Option<String>,
+    /// Review text, contains Tidal's `[wimpLink]` markup.
+    pub text: Option<String>,
+    pub summary: Option<String>,
+}
+
+impl Album {
+    /// Whether the album can be played now: streaming is allowed, the stream is ready and its
+    /// `stream_start_date`, when given, has passed. Missing flags count as not available.
+    pub fn is_available(&self) -> bool {
+        self.allow_streaming == Some(true)
+            && self.stream_ready == Some(true)
+            && self.stream_start_date.as_deref().map_or(true, is_past)
+    }
+
+    /// `duration` as a `Duration`, Tidal sends it in seconds.
+    pub fn duration_secs(&self) -> Option<Duration> {
+        self.duration.map(|duration| Duration::from_secs(u64::from(duration)))
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn release_date_parsed(&self) -> Option<NaiveDate> {
+        self.release_date.as_deref().and_then(crate::model::parse_date)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn stream_start_date_parsed(&self) -> Option<DateTime<Utc>> {
+        self.stream_start_date.as_deref().and_then(crate::model::parse_timestamp)
+    }
+
+    /// URL for the square album cover.
+    /// Valid sizes are 80, 160, 320, 640 and 1280.
+    pub fn cover_url(&self, size: u16) -> Option<String> {
+        self.cover.as_ref().map(|cover| image_url(cover, size, size))
+    }
+}
+
+/// Whether `album` and every one of its `tracks` can be streamed. Albums and tracks without an
+/// `allow_streaming` flag count as not streamable, so does an album without tracks.
+pub fn is_fully_streamable(album: &Album, tracks: &[Track]) -> bool {
+    album.allow_streaming == Some(true)
+        && !tracks.is_empty()
+        && tracks.iter().all(|track| track.allow_streaming == Some(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn availability() {
+        let album = |stream_start_date: Option<&str>| Album {
+            allow_streaming: Some(true),
+            stream_ready: Some(true),
+            stream_start_date: stream_start_date.map(ToOwned::to_owned),
+            ..Default::default()
+        };
+
+        assert!(album(Some("2017-10-20")).is_available());
+        assert!(!album(Some("2999-01-01T00:00:00.000+0000")).is_available());
+        assert!(album(None).is_available());
+        assert!(!Album { allow_streaming: Some(false), ..album(None) }.is_available());
+        assert!(!Album::default().is_available());
+    }
+
+    #[test]
+    fn fully_streamable() {
+        let album = |allow_streaming| Album { allow_streaming, ..Default::default() };
+        let track = |allow_streaming| Track { allow_streaming, ..Default::default() };
+
+        assert!(is_fully_streamable(&album(Some(true)), &[track(Some(true)), track(Some(true))]));
+        assert!(!is_fully_streamable(&album(Some(true)), &[track(Some(true)), track(None)]));
+        assert!(!is_fully_streamable(&album(Some(true)), &[track(Some(false))]));
+        assert!(!is_fully_streamable(&album(None), &[track(Some(true))]));
+        assert!(!is_fully_streamable(&album(Some(true)), &[]));
+    }
+}
@@ -1,25 +1,64 @@
 // Use 3rd party
 use serde::{Deserialize, Serialize};
 
+// Use built-in library
+use std::collections::HashMap;
+
 // Use local
-use crate::model::ModelType;
+use crate::model::{de_u32_flexible, ModelType};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum ArtistType {
     Artist,
     Contributor,
+    /// A value this version of the crate doesn't know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistRole {
+    pub category_id: Option<i32>,
+    pub category: Option<String>,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct Artist {
+    #[serde(default, deserialize_with = "de_u32_flexible")]
     pub id: Option<u32>,
     pub name: Option<String>,
-    #[serde(rename(deserialize = "artist_types"))]
+    #[serde(rename = "artistTypes")]
     pub artist_types: Option<Vec<ArtistType>>,
+    #[serde(rename = "artistRoles")]
+    pub artist_roles: Option<Vec<ArtistRole>>,
     pub url: Option<String>,
     pub picture: Option<String>,
     pub popularity: Option<u16>,
     #[serde(rename = "type")]
     pub _type: Option<ModelType>,
+    /// Mix ids seeded by this artist, keyed by mix type (e.g. `ARTIST_MIX`).
+    pub mixes: Option<HashMap<String, String>>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistBio {
+    pub source: Option<String>,
+    pub last_updated: Option<String>,
+    /// Biography text, contains Tidal's `[wimpLink]` markup.
+    pub text: Option<String>,
+    pub summary: Option<String>,
+}
+
+/// Link to a website of the artist, e.g. the official homepage or a social network.
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalLink {
+    pub url: Option<String>,
+    /// Kind of site, e.g. `OFFICIAL_HOMEPAGE` or `FACEBOOK`.
+    pub site_name: Option<String>,
+    /// Provider of the link, e.g. `TiVo`. Tidal sends it once for all links.
+    pub source: Option<String>,
 }
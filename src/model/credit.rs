@@ -0,0 +1,56 @@
+// Use 3rd party
+use serde::{Deserialize, Serialize};
+
+// Use local
+use crate::model::de_u32_flexible;
+
+/// A person credited for an album or track, e.g. in `Albums::credits` or
+/// `TidalSearch::contributors`.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct Contributor {
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "de_u32_flexible")]
+    pub id: Option<u32>,
+    /// The role, e.g. `Producer`, only sent where the contributor isn't grouped by role.
+    pub role: Option<String>,
+    /// Image id of the contributor's picture, only sent for contributors with an artist page.
+    pub picture: Option<String>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct Credit {
+    /// The role, e.g. `Producer` or `Composer`.
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub contributors: Vec<Contributor>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct TrackContributor {
+    pub name: Option<String>,
+    /// The role, e.g. `Lyricist` or `Mixing Engineer`.
+    pub role: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_contributor() {
+        let contributor: Contributor = serde_json::from_str(
+            r#"{
+                "id": 3650426,
+                "name": "Josh Wilbur",
+                "role": "Producer",
+                "picture": "1a7e0f3a-3c0e-4b5c-9f4e-2d1c5b8a9e07"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(contributor.id, Some(3650426));
+        assert_eq!(contributor.name, Some("Josh Wilbur".to_owned()));
+        assert_eq!(contributor.role, Some("Producer".to_owned()));
+        assert_eq!(contributor.picture, Some("1a7e0f3a-3c0e-4b5c-9f4e-2d1c5b8a9e07".to_owned()));
+    }
+}
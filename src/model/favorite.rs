@@ -0,0 +1,41 @@
+// Use 3rd party
+use serde::Deserialize;
+
+// Use built-in library
+use std::collections::HashSet;
+
+// Tidal returns every id as a string, grouped by type
+#[derive(Default, Debug, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct RawFavoriteIds {
+    #[serde(default)]
+    track: Vec<String>,
+    #[serde(default)]
+    album: Vec<String>,
+    #[serde(default)]
+    artist: Vec<String>,
+    #[serde(default)]
+    playlist: Vec<String>,
+}
+
+/// Ids of everything the user added to their favorites.
+#[derive(Default, Debug, Deserialize)]
+#[serde(from = "RawFavoriteIds")]
+pub struct FavoriteIds {
+    pub tracks: HashSet<u32>,
+    pub albums: HashSet<u32>,
+    pub artists: HashSet<u32>,
+    pub playlists: HashSet<String>,
+}
+
+impl From<RawFavoriteIds> for FavoriteIds {
+    fn from(raw: RawFavoriteIds) -> Self {
+        let parse = |ids: Vec<String>| ids.iter().filter_map(|id| id.parse().ok()).collect();
+        Self {
+            tracks: parse(raw.track),
+            albums: parse(raw.album),
+            artists: parse(raw.artist),
+            playlists: raw.playlist.into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+// Use 3rd party
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Genre {
+    pub name: Option<String>,
+    /// Identifier of the genre in the `/genres/{path}/...` endpoints.
+    pub path: Option<String>,
+    pub has_playlists: Option<bool>,
+    pub has_artists: Option<bool>,
+    pub has_albums: Option<bool>,
+    pub has_tracks: Option<bool>,
+}
@@ -0,0 +1,24 @@
+// Use 3rd party
+use serde::{Deserialize, Serialize};
+
+// Use built-in library
+use std::collections::HashMap;
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct MixImage {
+    pub url: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mix {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub sub_title: Option<String>,
+    /// Kind of mix, e.g. `DISCOVERY_MIX` or `TRACK_MIX`.
+    pub mix_type: Option<String>,
+    /// Images of the mix keyed by size, e.g. `SMALL`, `MEDIUM` and `LARGE`.
+    pub images: Option<HashMap<String, MixImage>>,
+}
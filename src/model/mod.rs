@@ -1,11 +1,121 @@
 pub mod album;
 pub mod artist;
+pub mod credit;
+pub mod favorite;
+pub mod genre;
+pub mod mix;
+pub mod playback;
 pub mod playlist;
+pub mod row;
 pub mod track;
+pub mod user;
+pub mod video;
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+
+/// Expands an opaque Tidal image id (e.g. `cover`, `picture`, `image`) into a resource URL.
+pub(crate) fn image_url(id: &str, width: u16, height: u16) -> String {
+    format!(
+        "https://resources.tidal.com/images/{}/{}x{}.jpg",
+        id.replace('-', "/"),
+        width,
+        height
+    )
+}
+
+/// Deserializes an optional id sent either as a number (`37312`) or a numeric string (`"37312"`).
+pub(crate) fn de_u32_flexible<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flexible {
+        Number(u32),
+        String(String),
+    }
+
+    match Option::<Flexible>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Flexible::Number(id)) => Ok(Some(id)),
+        Some(Flexible::String(id)) => id.parse().map(Some).map_err(de::Error::custom),
+    }
+}
+
+/// Deserializes an optional string borrowing it from the input, `Option<Cow<str>>` alone would
+/// always copy it.
+pub(crate) fn de_borrowed_str<'de: 'a, 'a, D>(
+    deserializer: D,
+) -> Result<Option<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Borrowed>::deserialize(deserializer)?.map(|borrowed| borrowed.0))
+}
+
+/// Parses Tidal's timestamps (e.g. `2020-09-19T16:48:19.032+0000`), dates without a time are
+/// taken as midnight UTC.
+#[cfg(feature = "chrono")]
+pub(crate) fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z")
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?))
+        })
+}
+
+/// Parses Tidal's dates (e.g. `2017-10-20`), the time of full timestamps is dropped.
+#[cfg(feature = "chrono")]
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_timestamp(value).map(|timestamp| timestamp.naive_utc().date()))
+}
+
+/// Whether a Tidal timestamp or date lies in the past, unparsable values never do.
+#[cfg(feature = "chrono")]
+pub(crate) fn is_past(value: &str) -> bool {
+    parse_timestamp(value).map_or(false, |timestamp| timestamp <= Utc::now())
+}
+
+/// Whether a Tidal timestamp or date lies in the past. Without chrono only the `YYYY-MM-DD`
+/// prefix is compared as a string with today's UTC date, so today's dates count as past.
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn is_past(value: &str) -> bool {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+    let date = value.get(..10).unwrap_or_default();
+    let is_date = date.len() == 10 && date.bytes().take(4).all(|byte| byte.is_ascii_digit());
+    is_date && date <= civil_date(seconds / 86400).as_str()
+}
+
+// Formats days since 1970-01-01 as `YYYY-MM-DD`, see
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+#[cfg(not(feature = "chrono"))]
+fn civil_date(days: u64) -> String {
+    let days = days + 719_468;
+    let (era, day_of_era) = (days / 146_097, days % 146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum ModelType {
     Album,
@@ -15,6 +125,27 @@ pub enum ModelType {
     User,
     Podcast,
     Contributor,
+    Playlist,
+    Track,
+    Video,
+    /// A value this version of the crate doesn't know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ModelType {
+    /// Name of the result bucket for this type in Tidal's search, `None` for types that can't be
+    /// searched.
+    pub fn search_type(&self) -> Option<&'static str> {
+        match self {
+            Self::Album => Some("ALBUMS"),
+            Self::Artist => Some("ARTISTS"),
+            Self::Playlist => Some("PLAYLISTS"),
+            Self::Track => Some("TRACKS"),
+            Self::Video => Some("VIDEOS"),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +157,9 @@ pub enum AudioMode {
     Sony360RealityAudio,
     #[serde(rename = "DOLBY_ATMOS")]
     DolbyAtmos,
+    /// A value this version of the crate doesn't know about yet.
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,4 +170,84 @@ pub enum AudioQuality {
     Master,
     High,
     Low,
+    /// A value this version of the crate doesn't know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::track::Track;
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parse_dates() {
+        use crate::model::album::Album;
+        use crate::model::playlist::Playlist;
+
+        let release_date = NaiveDate::from_ymd_opt(2017, 10, 20).unwrap();
+        let album: Album = serde_json::from_str(r#"{"releaseDate": "2017-10-20"}"#).unwrap();
+        assert_eq!(album.release_date_parsed(), Some(release_date));
+
+        let playlist: Playlist =
+            serde_json::from_str(r#"{"lastUpdated": "2020-09-19T16:48:19.032+0000"}"#).unwrap();
+        let last_updated = NaiveDate::from_ymd_opt(2020, 9, 19)
+            .and_then(|date| date.and_hms_milli_opt(16, 48, 19, 32))
+            .unwrap();
+        assert_eq!(playlist.last_updated_parsed(), Some(Utc.from_utc_datetime(&last_updated)));
+        assert_eq!(playlist.created_parsed(), None);
+
+        let midnight = Utc.from_utc_datetime(&release_date.and_hms_opt(0, 0, 0).unwrap());
+        assert_eq!(parse_timestamp("2017-10-20"), Some(midnight));
+        assert_eq!(parse_date("2017-10-20T00:00:00.000+0000"), Some(release_date));
+        assert_eq!(parse_date("not a date"), None);
+    }
+
+    #[test]
+    fn past_dates() {
+        assert!(is_past("2011-08-09T00:00:00.000+0000"));
+        assert!(is_past("2011-08-09"));
+        assert!(!is_past("2999-01-01T00:00:00.000+0000"));
+        assert!(!is_past("not a date"));
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(11_016), "2000-02-29");
+        assert_eq!(civil_date(11_017), "2000-03-01");
+        assert_eq!(civil_date(18_627), "2020-12-31");
+        assert_eq!(civil_date(47_541), "2100-03-01");
+    }
+
+    #[test]
+    fn flexible_ids() {
+        use crate::model::artist::Artist;
+
+        let artist: Artist = serde_json::from_str(r#"{"id": "37312"}"#).unwrap();
+        assert_eq!(artist.id, Some(37312));
+        let artist: Artist = serde_json::from_str(r#"{"id": 37312}"#).unwrap();
+        assert_eq!(artist.id, Some(37312));
+        let artist: Artist = serde_json::from_str(r#"{"id": null}"#).unwrap();
+        assert_eq!(artist.id, None);
+        let artist: Artist = serde_json::from_str(r#"{"name": "Muse"}"#).unwrap();
+        assert_eq!(artist.id, None);
+        assert!(serde_json::from_str::<Artist>(r#"{"id": "muse"}"#).is_err());
+    }
+
+    #[test]
+    fn unknown_variants() {
+        let track: Track = serde_json::from_str(
+            r#"{"audioQuality": "BOGUS_QUALITY", "audioModes": ["STEREO", "BOGUS_MODE"]}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(track.audio_quality, Some(AudioQuality::Unknown)));
+        assert!(matches!(track.audio_modes.unwrap()[1], AudioMode::Unknown));
+
+        let model_type: ModelType = serde_json::from_str(r#""BOGUS_TYPE""#).unwrap();
+        assert!(matches!(model_type, ModelType::Unknown));
+    }
 }
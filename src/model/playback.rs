@@ -0,0 +1,37 @@
+// Use 3rd party
+use serde::{Deserialize, Serialize};
+
+// Use local
+use crate::model::AudioQuality;
+
+/// Where and how to stream a track.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct StreamInfo {
+    pub track_id: Option<u32>,
+    /// First of `urls`, the one to use unless it fails.
+    pub url: Option<String>,
+    pub urls: Vec<String>,
+    pub codec: Option<String>,
+    pub encryption_type: Option<String>,
+    pub sound_quality: Option<AudioQuality>,
+}
+
+// Response of `/tracks/{id}/playbackinfopostpaywall`, the stream itself is described by the
+// base64 encoded manifest.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PlaybackInfo {
+    pub track_id: Option<u32>,
+    pub audio_quality: Option<AudioQuality>,
+    pub manifest_mime_type: String,
+    pub manifest: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StreamManifest {
+    pub codecs: Option<String>,
+    pub encryption_type: Option<String>,
+    #[serde(default)]
+    pub urls: Vec<String>,
+}
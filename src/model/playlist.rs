@@ -1,9 +1,28 @@
 // Use 3rd party
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
 // Use local
 use crate::model::artist::Artist;
-use crate::model::ModelType;
+use crate::model::track::Track;
+use crate::model::video::Video;
+use crate::model::{de_u32_flexible, image_url, ModelType};
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct PlaylistCreator {
+    #[serde(default, deserialize_with = "de_u32_flexible")]
+    pub id: Option<u32>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CreatorType {
+    /// Playlists curated by Tidal, their creator id is 0.
+    Editorial,
+    User,
+}
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,6 +31,7 @@ pub struct Playlist {
     pub title: Option<String>,
     pub number_of_tracks: Option<u32>,
     pub number_of_videos: Option<u32>,
+    pub creator: Option<PlaylistCreator>,
     pub description: Option<String>,
     pub duration: Option<u32>,
     pub last_updated: Option<String>,
@@ -25,3 +45,130 @@ pub struct Playlist {
     pub promoted_artists: Option<Vec<Artist>>,
     pub last_item_added_at: Option<String>,
 }
+
+/// A folder of playlists in the user's collection.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct PlaylistFolder {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    /// Number of playlists and folders in the folder.
+    #[serde(rename = "totalNumberOfItems")]
+    pub item_count: Option<u32>,
+    #[serde(rename = "createdAt")]
+    pub created: Option<String>,
+}
+
+/// A track of a playlist together with its position and when it was added.
+#[derive(Default, Debug)]
+pub struct PlaylistItem {
+    pub item: Track,
+    pub date_added: Option<String>,
+    /// Position of the item in the playlist, as used by `Playlists::move_item`.
+    pub index: Option<u32>,
+}
+
+/// An item of a playlist, playlists can contain videos as well as tracks.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "item", rename_all = "lowercase")]
+pub enum PlaylistEntry {
+    Track(Track),
+    Video(Video),
+}
+
+/// Response of adding tracks to a playlist.
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddTracksResult {
+    pub last_updated: u64,
+    /// Ids of the tracks that were added, duplicates are missing unless they were allowed.
+    pub added_item_ids: Vec<u32>,
+}
+
+/// Track ids of two playlists compared with each other.
+#[derive(Default, Debug, PartialEq)]
+pub struct PlaylistDiff {
+    pub only_in_a: Vec<u32>,
+    pub only_in_b: Vec<u32>,
+    pub common: Vec<u32>,
+}
+
+/// Number of tracks changed by syncing one playlist into another, `added` counts the tracks Tidal
+/// reports as added.
+#[derive(Default, Debug, PartialEq)]
+pub struct SyncReport {
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl Playlist {
+    pub fn creator_type(&self) -> Option<CreatorType> {
+        match self.creator.as_ref()?.id? {
+            0 => Some(CreatorType::Editorial),
+            _ => Some(CreatorType::User),
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn created_parsed(&self) -> Option<DateTime<Utc>> {
+        self.created.as_deref().and_then(crate::model::parse_timestamp)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn last_updated_parsed(&self) -> Option<DateTime<Utc>> {
+        self.last_updated.as_deref().and_then(crate::model::parse_timestamp)
+    }
+
+    /// URL for the rectangular playlist image. `size` is the width, the height is derived from
+    /// Tidal's 3:2 aspect ratio. Valid widths are 160, 480, 750 and 1080.
+    pub fn image_url(&self, size: u16) -> Option<String> {
+        self.image
+            .as_ref()
+            .map(|image| image_url(image, size, ((u32::from(size) * 2 + 1) / 3) as u16))
+    }
+
+    /// URL for the square playlist image.
+    /// Valid sizes are 160, 320, 480, 640, 750 and 1080.
+    pub fn square_image_url(&self, size: u16) -> Option<String> {
+        self.square_image
+            .as_ref()
+            .map(|image| image_url(image, size, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_long_duration() {
+        // Longer than `u16::MAX` seconds
+        let playlist: Playlist = serde_json::from_str(r#"{"duration": 90000}"#).unwrap();
+        assert_eq!(playlist.duration, Some(90000));
+    }
+
+    #[test]
+    fn playlist_image_url() {
+        let playlist = Playlist {
+            image: Some("443331e2-0e53-4e5a-b4c2-0d5d2a4e3f7b".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            playlist.image_url(750).unwrap(),
+            "https://resources.tidal.com/images/443331e2/0e53/4e5a/b4c2/0d5d2a4e3f7b/750x500.jpg"
+        );
+        assert_eq!(Playlist::default().image_url(750), None);
+    }
+
+    #[test]
+    fn playlist_square_image_url() {
+        let playlist = Playlist {
+            square_image: Some("cfa9bd8a-5e4e-4a40-9b1b-8c5f8b3a6a1e".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            playlist.square_image_url(320).unwrap(),
+            "https://resources.tidal.com/images/cfa9bd8a/5e4e/4a40/9b1b/8c5f8b3a6a1e/320x320.jpg"
+        );
+        assert_eq!(Playlist::default().square_image_url(320), None);
+    }
+}
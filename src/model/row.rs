@@ -0,0 +1,161 @@
+//! Lightweight views of the models for rendering lists. Rows borrow from the model they were
+//! created from, so creating them doesn't clone any strings.
+
+use crate::model::album::Album;
+use crate::model::artist::Artist;
+use crate::model::playlist::Playlist;
+use crate::model::track::Track;
+
+#[derive(Debug, PartialEq)]
+pub struct TrackRow<'a> {
+    pub id: Option<u32>,
+    pub title: Option<&'a str>,
+    pub artist_name: Option<&'a str>,
+    pub duration: Option<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AlbumRow<'a> {
+    pub id: Option<u32>,
+    pub title: Option<&'a str>,
+    pub artist_name: Option<&'a str>,
+    pub cover: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ArtistRow<'a> {
+    pub id: Option<u32>,
+    pub name: Option<&'a str>,
+    pub picture: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PlaylistRow<'a> {
+    pub uuid: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub number_of_tracks: Option<u32>,
+    pub square_image: Option<&'a str>,
+}
+
+// Name of the first artist in a list of artists
+fn first_artist_name(artists: &Option<Vec<Artist>>) -> Option<&str> {
+    artists.as_ref()?.first()?.name.as_deref()
+}
+
+impl<'a> From<&'a Track> for TrackRow<'a> {
+    fn from(track: &'a Track) -> Self {
+        let artist_name = track
+            .artist
+            .as_ref()
+            .and_then(|artist| artist.name.as_deref())
+            .or_else(|| first_artist_name(&track.artists));
+
+        Self {
+            id: track.id,
+            title: track.title.as_deref(),
+            artist_name,
+            duration: track.duration,
+        }
+    }
+}
+
+impl<'a> From<&'a Album> for AlbumRow<'a> {
+    fn from(album: &'a Album) -> Self {
+        Self {
+            id: album.id,
+            title: album.title.as_deref(),
+            artist_name: first_artist_name(&album.artists),
+            cover: album.cover.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a Artist> for ArtistRow<'a> {
+    fn from(artist: &'a Artist) -> Self {
+        Self {
+            id: artist.id,
+            name: artist.name.as_deref(),
+            picture: artist.picture.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a Playlist> for PlaylistRow<'a> {
+    fn from(playlist: &'a Playlist) -> Self {
+        Self {
+            uuid: playlist.uuid.as_deref(),
+            title: playlist.title.as_deref(),
+            number_of_tracks: playlist.number_of_tracks,
+            square_image: playlist.square_image.as_deref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_row() {
+        let track = Track {
+            id: Some(79914999),
+            title: Some("The Sin and the Sentence".to_owned()),
+            duration: Some(349),
+            artists: Some(vec![Artist {
+                name: Some("Trivium".to_owned()),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            TrackRow::from(&track),
+            TrackRow {
+                id: Some(79914999),
+                title: Some("The Sin and the Sentence"),
+                artist_name: Some("Trivium"),
+                duration: Some(349),
+            }
+        );
+    }
+
+    #[test]
+    fn album_row() {
+        let album = Album {
+            id: Some(79914998),
+            title: Some("My Album".to_owned()),
+            ..Default::default()
+        };
+
+        let row = AlbumRow::from(&album);
+        assert_eq!(row.id, Some(79914998));
+        assert_eq!(row.title, Some("My Album"));
+        assert_eq!(row.artist_name, None);
+    }
+
+    #[test]
+    fn artist_row() {
+        let artist = Artist {
+            id: Some(37312),
+            name: Some("Trivium".to_owned()),
+            ..Default::default()
+        };
+
+        let row = ArtistRow::from(&artist);
+        assert_eq!(row.id, Some(37312));
+        assert_eq!(row.name, Some("Trivium"));
+    }
+
+    #[test]
+    fn playlist_row() {
+        let playlist = Playlist {
+            uuid: Some("7ce7df87-6d37-4465-80db-84535a4e44a4".to_owned()),
+            number_of_tracks: Some(25),
+            ..Default::default()
+        };
+
+        let row = PlaylistRow::from(&playlist);
+        assert_eq!(row.uuid, Some("7ce7df87-6d37-4465-80db-84535a4e44a4"));
+        assert_eq!(row.number_of_tracks, Some(25));
+    }
+}
@@ -1,13 +1,22 @@
 // Use 3rd party
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
+// Use built-in library
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::Duration;
+
 use crate::model::album::Album;
 use crate::model::artist::Artist;
-use crate::model::{AudioMode, AudioQuality};
+use crate::model::{de_borrowed_str, de_u32_flexible, is_past, AudioMode, AudioQuality};
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Track {
+    #[serde(default, deserialize_with = "de_u32_flexible")]
     pub id: Option<u32>,
     pub title: Option<String>,
     pub duration: Option<u32>,
@@ -27,8 +36,221 @@ pub struct Track {
     pub editable: Option<bool>,
     pub explicit: Option<bool>,
     pub audio_quality: Option<AudioQuality>,
-    pub audio_modes: Vec<Option<AudioMode>>,
+    pub audio_modes: Option<Vec<AudioMode>>,
     pub artist: Option<Artist>,
-    pub artists: Vec<Option<Artist>>,
+    pub artists: Option<Vec<Artist>>,
     pub album: Option<Album>,
+    /// Mix ids seeded by this track, keyed by mix type (e.g. `TRACK_MIX`).
+    pub mixes: Option<HashMap<String, String>>,
+}
+
+impl Track {
+    /// Whether the track can be played now: streaming is allowed, the stream is ready and its
+    /// `stream_start_date`, when given, has passed. Missing flags count as not available.
+    pub fn is_available(&self) -> bool {
+        self.allow_streaming == Some(true)
+            && self.stream_ready == Some(true)
+            && self.stream_start_date.as_deref().map_or(true, is_past)
+    }
+
+    /// `duration` as a `Duration`, Tidal sends it in seconds.
+    pub fn duration_secs(&self) -> Option<Duration> {
+        self.duration.map(|duration| Duration::from_secs(u64::from(duration)))
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn stream_start_date_parsed(&self) -> Option<DateTime<Utc>> {
+        self.stream_start_date.as_deref().and_then(crate::model::parse_timestamp)
+    }
+
+    /// Linear factor to scale the samples of the track with for loudness normalization.
+    /// `target_db` is added to the track's `replay_gain` as pre-amp, 0.0 plays the track at
+    /// Tidal's reference loudness. The factor is clamped to `1.0 / peak` so the track never
+    /// clips. `None` when the track has no `replay_gain` or `peak`.
+    pub fn volume_adjustment(&self, target_db: f32) -> Option<f32> {
+        let replay_gain = self.replay_gain?;
+        let peak = self.peak?;
+
+        let gain = 10f32.powf((replay_gain + target_db) / 20.0);
+        if peak > 0.0 {
+            Some(gain.min(1.0 / peak))
+        } else {
+            Some(gain)
+        }
+    }
+}
+
+/// The most used fields of a `Track`, borrowing its strings from the response where possible.
+/// Parsing large responses, e.g. the tracks of a big playlist, into it allocates a lot less.
+/// Strings containing escapes are copied.
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackLite<'a> {
+    #[serde(default, deserialize_with = "de_u32_flexible")]
+    pub id: Option<u32>,
+    #[serde(borrow, default, deserialize_with = "de_borrowed_str")]
+    pub title: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "de_borrowed_str")]
+    pub version: Option<Cow<'a, str>>,
+    pub duration: Option<u32>,
+    pub track_number: Option<u16>,
+    pub volume_number: Option<u16>,
+    pub explicit: Option<bool>,
+    pub allow_streaming: Option<bool>,
+}
+
+/// Id of a track to add to a playlist, either a bare id or taken from a `Track`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackId(pub Option<u32>);
+
+impl From<u32> for TrackId {
+    fn from(id: u32) -> Self {
+        Self(Some(id))
+    }
+}
+
+impl From<&Track> for TrackId {
+    fn from(track: &Track) -> Self {
+        Self(track.id)
+    }
+}
+
+impl From<Track> for TrackId {
+    fn from(track: Track) -> Self {
+        Self(track.id)
+    }
+}
+
+/// Whether any of `tracks` is explicit, tracks without an `explicit` flag count as clean.
+pub fn any_explicit(tracks: &[Track]) -> bool {
+    tracks.iter().any(|track| track.explicit == Some(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrowed_tracks_match_owned_tracks() {
+        use crate::client::{Tidal, TidalItems};
+
+        let input = std::fs::read_to_string("tests/files/playlist_tracks.json").unwrap();
+        let owned = Tidal::convert_result::<TidalItems<Track>>(&input).unwrap().items;
+        let borrowed = Tidal::convert_result::<TidalItems<TrackLite>>(&input).unwrap().items;
+
+        assert_eq!(owned.len(), borrowed.len());
+        for (track, lite) in owned.iter().zip(&borrowed) {
+            assert_eq!(track.id, lite.id);
+            assert_eq!(track.title.as_deref(), lite.title.as_deref());
+            assert_eq!(track.version.as_deref(), lite.version.as_deref());
+            assert_eq!(track.duration, lite.duration);
+            assert_eq!(track.track_number, lite.track_number);
+            assert_eq!(track.volume_number, lite.volume_number);
+            assert_eq!(track.explicit, lite.explicit);
+            assert_eq!(track.allow_streaming, lite.allow_streaming);
+        }
+        assert!(matches!(borrowed[0].title, Some(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn availability() {
+        let track = |stream_start_date: Option<&str>| Track {
+            allow_streaming: Some(true),
+            stream_ready: Some(true),
+            stream_start_date: stream_start_date.map(ToOwned::to_owned),
+            ..Default::default()
+        };
+
+        assert!(track(Some("2011-08-09T00:00:00.000+0000")).is_available());
+        assert!(!track(Some("2999-01-01T00:00:00.000+0000")).is_available());
+        assert!(track(None).is_available());
+        assert!(!Track { stream_ready: None, ..track(None) }.is_available());
+        assert!(!Track::default().is_available());
+    }
+
+    #[test]
+    fn any_explicit_tracks() {
+        let track = |explicit| Track { explicit, ..Default::default() };
+
+        assert!(any_explicit(&[track(Some(false)), track(None), track(Some(true))]));
+        assert!(!any_explicit(&[track(Some(false)), track(None)]));
+        assert!(!any_explicit(&[]));
+    }
+
+    #[test]
+    fn deserialize_audio_modes_and_artists() {
+        let track: Track = serde_json::from_str(
+            r#"{
+                "id": 79914999,
+                "audioModes": ["STEREO"],
+                "artists": [
+                    {"id": 37312, "name": "Trivium", "type": "MAIN"},
+                    {"id": 3635103, "name": "Asking Alexandria", "type": "MAIN"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let audio_modes = track.audio_modes.unwrap();
+        assert_eq!(audio_modes.len(), 1);
+        assert!(matches!(audio_modes[0], AudioMode::Stereo));
+        let artists = track.artists.unwrap();
+        assert_eq!(artists.len(), 2);
+        assert_eq!(artists[1].name, Some("Asking Alexandria".to_owned()));
+    }
+
+    #[test]
+    fn duration_secs() {
+        let track: Track = serde_json::from_str(r#"{"id": 79914999, "duration": 316}"#).unwrap();
+        assert_eq!(track.duration_secs(), Some(Duration::from_secs(316)));
+        assert_eq!(Track::default().duration_secs(), None);
+    }
+
+    #[test]
+    fn volume_adjustment() {
+        let track = Track {
+            replay_gain: Some(-11.67),
+            peak: Some(1.0),
+            ..Default::default()
+        };
+        let gain = track.volume_adjustment(0.0).unwrap();
+        assert!((gain - 0.2609).abs() < 0.001);
+        let gain = track.volume_adjustment(6.0).unwrap();
+        assert!((gain - 0.5206).abs() < 0.001);
+    }
+
+    #[test]
+    fn volume_adjustment_never_clips() {
+        let track = Track {
+            replay_gain: Some(6.0),
+            peak: Some(0.9),
+            ..Default::default()
+        };
+        let gain = track.volume_adjustment(0.0).unwrap();
+        assert!((gain - 1.0 / 0.9).abs() < f32::EPSILON);
+        assert!(gain * 0.9 <= 1.0 + f32::EPSILON);
+    }
+
+    #[test]
+    fn volume_adjustment_missing_fields() {
+        let track = Track {
+            replay_gain: Some(-11.67),
+            ..Default::default()
+        };
+        assert!(track.volume_adjustment(0.0).is_none());
+
+        let track = Track {
+            peak: Some(1.0),
+            ..Default::default()
+        };
+        assert!(track.volume_adjustment(0.0).is_none());
+    }
+
+    #[test]
+    fn deserialize_missing_audio_modes_and_artists() {
+        let track: Track = serde_json::from_str(r#"{"id": 79914999}"#).unwrap();
+
+        assert!(track.audio_modes.is_none());
+        assert!(track.artists.is_none());
+    }
 }
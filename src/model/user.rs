@@ -0,0 +1,22 @@
+// Use 3rd party
+use serde::{Deserialize, Serialize};
+
+// Use local
+use crate::model::de_u32_flexible;
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    #[serde(default, deserialize_with = "de_u32_flexible")]
+    pub id: Option<u32>,
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: Option<String>,
+    pub country_code: Option<String>,
+    pub created: Option<String>,
+    pub picture: Option<String>,
+    pub newsletter: Option<bool>,
+    pub gender: Option<String>,
+    pub date_of_birth: Option<String>,
+}
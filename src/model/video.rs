@@ -0,0 +1,41 @@
+// Use 3rd party
+use serde::{Deserialize, Serialize};
+
+// Use local
+use crate::model::album::Album;
+use crate::model::artist::Artist;
+use crate::model::{de_u32_flexible, image_url};
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Video {
+    #[serde(default, deserialize_with = "de_u32_flexible")]
+    pub id: Option<u32>,
+    pub title: Option<String>,
+    pub duration: Option<u32>,
+    pub image_id: Option<String>,
+    pub release_date: Option<String>,
+    pub stream_start_date: Option<String>,
+    pub allow_streaming: Option<bool>,
+    pub stream_ready: Option<bool>,
+    pub track_number: Option<u16>,
+    pub volume_number: Option<u16>,
+    pub popularity: Option<u32>,
+    pub explicit: Option<bool>,
+    pub url: Option<String>,
+    /// Resolution of the video, e.g. `MP4_1080P`.
+    pub quality: Option<String>,
+    pub artist: Option<Artist>,
+    pub artists: Option<Vec<Artist>>,
+    pub album: Option<Album>,
+}
+
+impl Video {
+    /// URL for the video thumbnail. `size` is the width, the height is derived from Tidal's 3:2
+    /// aspect ratio. Valid widths are 160, 480, 750 and 1080.
+    pub fn image_url(&self, size: u16) -> Option<String> {
+        self.image_id
+            .as_ref()
+            .map(|image| image_url(image, size, ((u32::from(size) * 2 + 1) / 3) as u16))
+    }
+}